@@ -0,0 +1,32 @@
+// Compare with and without SIMD:
+//   cargo bench
+//   cargo bench --features simd
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use raymarcher::{default_scene, vec3, vec4};
+use raymarcher::render::{render, render_packets};
+
+fn vectors(c: &mut Criterion) {
+    let a = vec3!(1.5, -2, 0.25);
+    let b = vec3!(-0.5, 3, 4);
+    c.bench_function("vec3 dot", |bench| bench.iter(|| black_box(a).dot(&black_box(b))));
+    c.bench_function("vec3 normalized", |bench| bench.iter(|| black_box(a).normalized()));
+    c.bench_function("vec3 mul add", |bench| bench.iter(|| black_box(a) * black_box(b) + black_box(a)));
+
+    let a = vec4!(1.5, -2, 0.25, 1);
+    let b = vec4!(-0.5, 3, 4, 2);
+    c.bench_function("vec4 dot", |bench| bench.iter(|| black_box(a).dot(&black_box(b))));
+    c.bench_function("vec4 normalized", |bench| bench.iter(|| black_box(a).normalized()));
+    c.bench_function("vec4 mul add", |bench| bench.iter(|| black_box(a) * black_box(b) + black_box(a)));
+}
+
+fn default_scene_render(c: &mut Criterion) {
+    let (port, scene) = default_scene(320, 180);
+    let mut group = c.benchmark_group("render");
+    group.sample_size(20);
+    group.bench_function("default scene 320x180", |bench| bench.iter(|| render(&port, &scene)));
+    group.bench_function("default scene 320x180 packets", |bench| bench.iter(|| render_packets(&port, &scene)));
+    group.finish();
+}
+
+criterion_group!(benches, vectors, default_scene_render);
+criterion_main!(benches);
@@ -0,0 +1,27 @@
+extern crate image;
+
+#[macro_use]
+pub mod linalg;
+pub mod render;
+pub mod objects;
+pub mod noise;
+
+use render::ViewPort;
+use objects::{Cube, Scene, Sphere, Subtract};
+
+// The scene the binary renders, shared with the benchmarks
+pub fn default_scene(width: u32, height: u32) -> (ViewPort, Scene) {
+    let port = ViewPort::new(
+        &vec3!(3, 3, 3),
+        &vec3!(-2, -3, -3),
+        &vec3!(0, 1, 0),
+        width,
+        height,
+        100.0
+    );
+    let mut scene = Scene::new();
+    let b = Cube::new(&vec3!(0.75));
+    let s = Sphere::new(1.0);
+    scene.add(Subtract(b, s));
+    (port, scene)
+}
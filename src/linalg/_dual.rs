@@ -0,0 +1,310 @@
+use std::cmp::Ordering;
+use std::fmt::Debug;
+use std::ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Neg, Sub, SubAssign, Rem};
+use crate::linalg::{Real, Scalar, Number, Vec3};
+
+// What a dual number carries next to its value. A single derivative for
+// Dual, or a whole gradient for Dual3
+pub trait Tangent: Copy + Debug + PartialEq + Add<Output = Self> + Sub<Output = Self> + Neg<Output = Self> + Mul<Real, Output = Self> {
+    fn zero() -> Self;
+}
+
+impl Tangent for Real {
+    fn zero() -> Self {
+        0.0
+    }
+}
+
+impl Tangent for Vec3 {
+    fn zero() -> Self {
+        vec3!(0)
+    }
+}
+
+// Forward mode automatic differentiation. Every operation applies the chain
+// rule to the derivative, so running a function on duals gives its value
+// and its exact derivative in the same pass
+#[derive(Debug, Clone, Copy)]
+pub struct Dual<D = Real> {
+    pub value: Real,
+    pub derivative: D
+}
+
+pub type Dual3 = Dual<Vec3>;
+
+impl<D: Tangent> Dual<D> {
+    pub fn new(value: Real, derivative: D) -> Self {
+        Self {value, derivative}
+    }
+
+    pub fn constant(value: Real) -> Self {
+        Self::new(value, D::zero())
+    }
+
+    // Applies f given its derivative df at the current value
+    fn chain(self, f: Real, df: Real) -> Self {
+        Self::new(f, self.derivative * df)
+    }
+
+    pub fn exp(self) -> Self {
+        let e = self.value.exp();
+        self.chain(e, e)
+    }
+
+    pub fn powi(self, n: i32) -> Self {
+        self.chain(self.value.powi(n), n as Real * self.value.powi(n - 1))
+    }
+}
+
+impl Dual {
+    // The input to differentiate with respect to
+    pub fn variable(value: Real) -> Self {
+        Self::new(value, 1.0)
+    }
+}
+
+impl Dual3 {
+    // Seeds a point so that results carry their gradient with respect to it
+    pub fn variables(p: &Vec3) -> Vec3<Self> {
+        Vec3::new(
+            Self::new(p.x, vec3!(1, 0, 0)),
+            Self::new(p.y, vec3!(0, 1, 0)),
+            Self::new(p.z, vec3!(0, 0, 1))
+        )
+    }
+
+    // The point the variables stand at
+    pub fn values(z: &Vec3<Self>) -> Vec3 {
+        vec3!(z.x.value, z.y.value, z.z.value)
+    }
+
+    // A value with gradient g at the point z stands at, carried by the chain
+    // rule through to whatever z was computed from
+    pub fn through(z: &Vec3<Self>, value: Real, g: &Vec3) -> Self {
+        Self::new(value, z.x.derivative * g.x + z.y.derivative * g.y + z.z.derivative * g.z)
+    }
+
+    pub fn gradient(&self) -> Vec3 {
+        self.derivative
+    }
+}
+
+// Compared and ordered by value alone, the same way a plain number would be,
+// so comparisons and min and max pick the branch the plain function would
+// have taken
+impl<D: Tangent> PartialEq for Dual<D> {
+    fn eq(&self, other: &Self) -> bool {
+        self.value == other.value
+    }
+}
+
+impl<D: Tangent> PartialOrd for Dual<D> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        self.value.partial_cmp(&other.value)
+    }
+}
+
+impl<D: Tangent> Add for Dual<D> {
+    type Output = Self;
+    fn add(self, rhs: Self) -> Self {
+        Self::new(self.value + rhs.value, self.derivative + rhs.derivative)
+    }
+}
+
+impl<D: Tangent> Sub for Dual<D> {
+    type Output = Self;
+    fn sub(self, rhs: Self) -> Self {
+        Self::new(self.value - rhs.value, self.derivative - rhs.derivative)
+    }
+}
+
+impl<D: Tangent> Mul for Dual<D> {
+    type Output = Self;
+    fn mul(self, rhs: Self) -> Self {
+        Self::new(self.value * rhs.value, self.derivative * rhs.value + rhs.derivative * self.value)
+    }
+}
+
+impl<D: Tangent> Div for Dual<D> {
+    type Output = Self;
+    fn div(self, rhs: Self) -> Self {
+        let inv = 1.0 / rhs.value;
+        Self::new(self.value * inv, (self.derivative - rhs.derivative * (self.value * inv)) * inv)
+    }
+}
+
+// a % b is a - b * trunc(a / b), where the truncated part is locally constant
+impl<D: Tangent> Rem for Dual<D> {
+    type Output = Self;
+    fn rem(self, rhs: Self) -> Self {
+        let n = (self.value / rhs.value).trunc();
+        Self::new(self.value % rhs.value, self.derivative - rhs.derivative * n)
+    }
+}
+
+impl<D: Tangent> Neg for Dual<D> {
+    type Output = Self;
+    fn neg(self) -> Self {
+        Self::new(-self.value, -self.derivative)
+    }
+}
+
+impl<D: Tangent> AddAssign for Dual<D> {
+    fn add_assign(&mut self, rhs: Self) {
+        *self = *self + rhs;
+    }
+}
+
+impl<D: Tangent> SubAssign for Dual<D> {
+    fn sub_assign(&mut self, rhs: Self) {
+        *self = *self - rhs;
+    }
+}
+
+impl<D: Tangent> MulAssign for Dual<D> {
+    fn mul_assign(&mut self, rhs: Self) {
+        *self = *self * rhs;
+    }
+}
+
+impl<D: Tangent> DivAssign for Dual<D> {
+    fn div_assign(&mut self, rhs: Self) {
+        *self = *self / rhs;
+    }
+}
+
+impl<D: Tangent> Scalar for Dual<D> {
+    fn from_f64(x: f64) -> Self {
+        Self::constant(Real::from_f64(x))
+    }
+
+    fn select_lt(self, other: Self, if_less: Self, otherwise: Self) -> Self {
+        if self.value < other.value { if_less } else { otherwise }
+    }
+
+    // Flat at zero rather than infinitely steep, so a zero length, like
+    // Cube's outside part for points inside it, drops out of the gradient
+    fn sqrt(self) -> Self {
+        let s = self.value.sqrt();
+        if s == 0.0 { Self::constant(s) } else { self.chain(s, 0.5 / s) }
+    }
+
+    fn abs(self) -> Self {
+        if self.value < 0.0 { -self } else { self }
+    }
+
+    fn min(self, other: Self) -> Self {
+        if other.value < self.value { other } else { self }
+    }
+
+    fn max(self, other: Self) -> Self {
+        if other.value > self.value { other } else { self }
+    }
+
+    // Steps are flat everywhere they're differentiable
+    fn floor(self) -> Self {
+        Self::constant(self.value.floor())
+    }
+
+    fn signum(self) -> Self {
+        Self::constant(self.value.signum())
+    }
+
+    fn sin(self) -> Self {
+        let (s, c) = self.value.sin_cos();
+        self.chain(s, c)
+    }
+
+    fn cos(self) -> Self {
+        let (s, c) = self.value.sin_cos();
+        self.chain(c, -s)
+    }
+
+    fn acos(self) -> Self {
+        self.chain(self.value.acos(), -1.0 / (1.0 - self.value * self.value).sqrt())
+    }
+
+    // The angle of (x, self), which turns with the point around the origin
+    fn atan2(self, x: Self) -> Self {
+        let r2 = x.value * x.value + self.value * self.value;
+        let derivative = (self.derivative * x.value - x.derivative * self.value) * (1.0 / r2);
+        Self::new(self.value.atan2(x.value), derivative)
+    }
+
+    // Through exp(n ln x), except that a constant exponent doesn't need ln,
+    // which keeps x = 0 working for the usual integer powers
+    fn powf(self, n: Self) -> Self {
+        let value = self.value.powf(n.value);
+        let d = self.derivative * (n.value * self.value.powf(n.value - 1.0));
+        if n.derivative == D::zero() {
+            Self::new(value, d)
+        } else {
+            Self::new(value, d + n.derivative * (value * self.value.ln()))
+        }
+    }
+
+    fn ln(self) -> Self {
+        self.chain(self.value.ln(), 1.0 / self.value)
+    }
+}
+
+impl<D: Tangent> Number for Dual<D> {
+    fn to_f64(self) -> f64 {
+        self.value.to_f64()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::linalg::approx::EPSILON;
+
+    fn close(a: Real, b: Real) -> bool {
+        (a - b).abs() < EPSILON
+    }
+
+    #[test]
+    fn product_and_quotient_rules() {
+        let x = Dual::variable(3.0);
+        let f = x * x * x / (x + Dual::constant(1.0));
+        // d/dx x^3 / (x + 1) = (2x^3 + 3x^2) / (x + 1)^2
+        assert!(close(f.value, 27.0 / 4.0));
+        assert!(close(f.derivative, (54.0 + 27.0) / 16.0));
+    }
+
+    #[test]
+    fn elementary_functions() {
+        let x = Dual::variable(0.5);
+        assert!(close(x.sin().derivative, 0.5f64.cos() as Real));
+        assert!(close(x.cos().derivative, -(0.5f64.sin() as Real)));
+        assert!(close(x.exp().derivative, x.exp().value));
+        assert!(close(x.ln().derivative, 2.0));
+        assert!(close(x.powi(3).derivative, 0.75));
+        assert!(close(x.sqrt().derivative, 0.5 / x.sqrt().value));
+    }
+
+    #[test]
+    fn gradient_of_length() {
+        let p = Dual3::variables(&vec3!(3, 0, 4));
+        let d = p.len();
+        assert_eq!(d.value, 5.0);
+        assert!((d.gradient() - vec3!(0.6, 0, 0.8)).len() < EPSILON);
+    }
+
+    #[test]
+    fn compared_by_value() {
+        let a = Dual::new(1.0, 2.0);
+        let b = Dual::new(1.0, 3.0);
+        assert_eq!(a, b);
+        assert_eq!(a.partial_cmp(&b), Some(Ordering::Equal));
+        assert!(Dual::new(2.0, 0.0) > b);
+    }
+
+    #[test]
+    fn min_follows_the_smaller_branch() {
+        let p = Dual3::variables(&vec3!(1, 2, 3));
+        assert_eq!(p.x.min(p.y).gradient(), vec3!(1, 0, 0));
+        assert_eq!(p.x.max(p.y).gradient(), vec3!(0, 1, 0));
+        assert_eq!((-p.z).abs().gradient(), vec3!(0, 0, 1));
+    }
+}
@@ -0,0 +1,90 @@
+use std::ops::{Add, AddAssign, Mul, MulAssign, Neg, Sub, SubAssign};
+use crate::linalg::{Real, Vec2};
+
+// Column major, like GLSL
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Mat2 {
+    pub cols: [Vec2; 2]
+}
+
+impl Mat2 {
+    pub fn new(c0: &Vec2, c1: &Vec2) -> Self {
+        Self {cols: [*c0, *c1]}
+    }
+
+    pub fn from_rows(r0: &Vec2, r1: &Vec2) -> Self {
+        Self::new(r0, r1).transpose()
+    }
+
+    pub fn identity() -> Self {
+        Self::scale(&vec2!(1))
+    }
+
+    pub fn scale(s: &Vec2) -> Self {
+        Self::new(&vec2!(s.x, 0), &vec2!(0, s.y))
+    }
+
+    // Counter-clockwise
+    pub fn rotation(angle: Real) -> Self {
+        let (s, c) = angle.sin_cos();
+        Self::new(&vec2!(c, s), &vec2!(-s, c))
+    }
+
+    pub fn transpose(&self) -> Self {
+        let [a, b] = self.cols;
+        Self::new(&vec2!(a.x, b.x), &vec2!(a.y, b.y))
+    }
+
+    pub fn determinant(&self) -> Real {
+        let [a, b] = self.cols;
+        a.x * b.y - b.x * a.y
+    }
+
+    // None when the matrix is singular
+    pub fn inverse(&self) -> Option<Self> {
+        let det = self.determinant();
+        if det == 0.0 {
+            return None;
+        }
+        let [a, b] = self.cols;
+        Some(Self::new(&vec2!(b.y, -a.y), &vec2!(-b.x, a.x)) * (1.0 / det))
+    }
+}
+
+impl_mat_ops!(Mat2, Vec2; [0 => x, 1 => y]);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::linalg::approx::EPSILON;
+
+    #[test]
+    fn rotation() {
+        let rotated = Mat2::rotation(crate::linalg::consts::FRAC_PI_2) * vec2!(1, 0);
+        assert!((rotated - vec2!(0, 1)).len() < EPSILON);
+    }
+
+    #[test]
+    fn from_rows() {
+        let m = Mat2::from_rows(&vec2!(1, 2), &vec2!(3, 4));
+        assert_eq!(m * vec2!(1, 1), vec2!(3, 7));
+        assert_eq!(vec2!(1, 1) * m, vec2!(4, 6));
+    }
+
+    #[test]
+    fn inverse() {
+        let m = Mat2::from_rows(&vec2!(4, 7), &vec2!(2, 6));
+        assert_eq!(m.determinant(), 10.0);
+        let product = m * m.inverse().unwrap();
+        for (col, id) in product.cols.iter().zip(Mat2::identity().cols.iter()) {
+            assert!((*col - *id).len() < EPSILON);
+        }
+        assert_eq!(Mat2::scale(&vec2!(1, 0)).inverse(), None);
+    }
+
+    generate_op_test!(add; Mat2::identity(), +, Mat2::identity() => Mat2::scale(&vec2!(2)));
+    generate_op_test!(sub; Mat2::identity(), -, Mat2::identity() => Mat2::scale(&vec2!(0)));
+    generate_op_test!(mul_float; Mat2::identity(), *, 3.0 => Mat2::scale(&vec2!(3)));
+    generate_op_test!(mul_mat; Mat2::scale(&vec2!(2, 3)), *, Mat2::scale(&vec2!(4, 5)) => Mat2::scale(&vec2!(8, 15)));
+    generate_op_test!((assign) mul_assign_vec; vec2!(1, 2), *=, Mat2::scale(&vec2!(2, 3)) => vec2!(2, 6));
+}
@@ -0,0 +1,146 @@
+use std::ops::{Add, AddAssign, Mul, MulAssign, Neg, Sub, SubAssign};
+use crate::linalg::{Real, Vec3};
+
+// Column major, like GLSL
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Mat3 {
+    pub cols: [Vec3; 3]
+}
+
+impl Mat3 {
+    pub fn new(c0: &Vec3, c1: &Vec3, c2: &Vec3) -> Self {
+        Self {cols: [*c0, *c1, *c2]}
+    }
+
+    pub fn from_rows(r0: &Vec3, r1: &Vec3, r2: &Vec3) -> Self {
+        Self::new(r0, r1, r2).transpose()
+    }
+
+    pub fn identity() -> Self {
+        Self::scale(&vec3!(1))
+    }
+
+    pub fn scale(s: &Vec3) -> Self {
+        Self::new(&vec3!(s.x, 0, 0), &vec3!(0, s.y, 0), &vec3!(0, 0, s.z))
+    }
+
+    // Rotations are counter-clockwise looking down the axis towards the origin
+    pub fn rotation_x(angle: Real) -> Self {
+        let (s, c) = angle.sin_cos();
+        Self::new(&vec3!(1, 0, 0), &vec3!(0, c, s), &vec3!(0, -s, c))
+    }
+
+    pub fn rotation_y(angle: Real) -> Self {
+        let (s, c) = angle.sin_cos();
+        Self::new(&vec3!(c, 0, -s), &vec3!(0, 1, 0), &vec3!(s, 0, c))
+    }
+
+    pub fn rotation_z(angle: Real) -> Self {
+        let (s, c) = angle.sin_cos();
+        Self::new(&vec3!(c, s, 0), &vec3!(-s, c, 0), &vec3!(0, 0, 1))
+    }
+
+    // Rodrigues' formula around any axis
+    pub fn rotation(axis: &Vec3, angle: Real) -> Self {
+        let a = axis.normalized();
+        let (s, c) = angle.sin_cos();
+        let t = 1.0 - c;
+        Self::new(
+            &vec3!(t * a.x * a.x + c, t * a.x * a.y + s * a.z, t * a.x * a.z - s * a.y),
+            &vec3!(t * a.x * a.y - s * a.z, t * a.y * a.y + c, t * a.y * a.z + s * a.x),
+            &vec3!(t * a.x * a.z + s * a.y, t * a.y * a.z - s * a.x, t * a.z * a.z + c)
+        )
+    }
+
+    // Orthonormal basis with columns right, up and forward, the same one
+    // ViewPort builds. Takes camera space to world space
+    pub fn look_at(forward: &Vec3, up: &Vec3) -> Self {
+        let forward = forward.normalized();
+        let right = forward.cross(up).normalized();
+        let up = right.cross(&forward);
+        Self::new(&right, &up, &forward)
+    }
+
+    pub fn transpose(&self) -> Self {
+        let [a, b, c] = self.cols;
+        Self::new(&vec3!(a.x, b.x, c.x), &vec3!(a.y, b.y, c.y), &vec3!(a.z, b.z, c.z))
+    }
+
+    pub fn determinant(&self) -> Real {
+        let [a, b, c] = self.cols;
+        a.dot(&b.cross(&c))
+    }
+
+    // None when the matrix is singular
+    pub fn inverse(&self) -> Option<Self> {
+        let det = self.determinant();
+        if det == 0.0 {
+            return None;
+        }
+        let [a, b, c] = self.cols;
+        Some(Self::from_rows(&b.cross(&c), &c.cross(&a), &a.cross(&b)) * (1.0 / det))
+    }
+}
+
+impl_mat_ops!(Mat3, Vec3; [0 => x, 1 => y, 2 => z]);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::linalg::approx::EPSILON;
+    use crate::linalg::consts::FRAC_PI_2;
+
+    fn assert_close(a: &Mat3, b: &Mat3) {
+        for (x, y) in a.cols.iter().zip(b.cols.iter()) {
+            assert!((*x - *y).len() < EPSILON, "{:?} != {:?}", a, b);
+        }
+    }
+
+    #[test]
+    fn axis_rotations() {
+        let (x, y, z) = (vec3!(1, 0, 0), vec3!(0, 1, 0), vec3!(0, 0, 1));
+        assert!((Mat3::rotation_x(FRAC_PI_2) * y - z).len() < EPSILON);
+        assert!((Mat3::rotation_y(FRAC_PI_2) * z - x).len() < EPSILON);
+        assert!((Mat3::rotation_z(FRAC_PI_2) * x - y).len() < EPSILON);
+        assert_close(&Mat3::rotation(&x, 0.3), &Mat3::rotation_x(0.3));
+        assert_close(&Mat3::rotation(&y, 0.3), &Mat3::rotation_y(0.3));
+        assert_close(&Mat3::rotation(&z, 0.3), &Mat3::rotation_z(0.3));
+    }
+
+    #[test]
+    fn rotations_are_orthonormal() {
+        let r = Mat3::rotation(&vec3!(1, 2, 3), 1.1);
+        assert_close(&(r * r.transpose()), &Mat3::identity());
+        assert!((r.determinant() - 1.0).abs() < EPSILON);
+    }
+
+    #[test]
+    fn inverse() {
+        let m = Mat3::from_rows(&vec3!(2, 0, 1), &vec3!(1, 3, 2), &vec3!(1, 1, 2));
+        assert_eq!(m.determinant(), 6.0);
+        assert_close(&(m * m.inverse().unwrap()), &Mat3::identity());
+        assert_eq!(Mat3::scale(&vec3!(1, 0, 1)).inverse(), None);
+    }
+
+    #[test]
+    fn look_at_matches_the_viewport() {
+        let m = Mat3::look_at(&vec3!(0, 0, -1), &vec3!(0, 1, 0));
+        assert_eq!(m * vec3!(0, 0, 1), vec3!(0, 0, -1));
+        assert_eq!(m * vec3!(1, 0, 0), vec3!(1, 0, 0));
+        assert_eq!(m * vec3!(0, 1, 0), vec3!(0, 1, 0));
+    }
+
+    #[test]
+    fn vector_on_the_left_is_transposed() {
+        let m = Mat3::from_rows(&vec3!(1, 2, 3), &vec3!(4, 5, 6), &vec3!(7, 8, 9));
+        let v = vec3!(1, 0, -1);
+        assert_eq!(v * m, m.transpose() * v);
+        assert_eq!(m * v, vec3!(-2, -2, -2));
+    }
+
+    generate_op_test!(add; Mat3::identity(), +, Mat3::identity() => Mat3::scale(&vec3!(2)));
+    generate_op_test!(sub; Mat3::identity(), -, Mat3::identity() => Mat3::scale(&vec3!(0)));
+    generate_op_test!(mul_float; Mat3::identity(), *, 3.0 => Mat3::scale(&vec3!(3)));
+    generate_op_test!(mul_mat; Mat3::scale(&vec3!(1, 2, 3)), *, Mat3::scale(&vec3!(4)) => Mat3::scale(&vec3!(4, 8, 12)));
+    generate_op_test!((assign) mul_assign_mat; Mat3::identity(), *=, Mat3::scale(&vec3!(2)) => Mat3::scale(&vec3!(2)));
+}
@@ -0,0 +1,188 @@
+use std::ops::{Add, AddAssign, Mul, MulAssign, Neg, Sub, SubAssign};
+use crate::linalg::{Real, Mat3, Vec3, Vec4};
+
+// Column major, like GLSL. Points are column vectors with w = 1
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Mat4 {
+    pub cols: [Vec4; 4]
+}
+
+impl Mat4 {
+    pub fn new(c0: &Vec4, c1: &Vec4, c2: &Vec4, c3: &Vec4) -> Self {
+        Self {cols: [*c0, *c1, *c2, *c3]}
+    }
+
+    pub fn from_rows(r0: &Vec4, r1: &Vec4, r2: &Vec4, r3: &Vec4) -> Self {
+        Self::new(r0, r1, r2, r3).transpose()
+    }
+
+    pub fn identity() -> Self {
+        Self::from_mat3(&Mat3::identity())
+    }
+
+    // Embeds a linear map in the upper left corner
+    pub fn from_mat3(m: &Mat3) -> Self {
+        let [a, b, c] = m.cols;
+        Self::new(&vec4!(v3 a, 0), &vec4!(v3 b, 0), &vec4!(v3 c, 0), &vec4!(0, 0, 0, 1))
+    }
+
+    pub fn scale(s: &Vec3) -> Self {
+        Self::from_mat3(&Mat3::scale(s))
+    }
+
+    pub fn translation(t: &Vec3) -> Self {
+        let mut m = Self::identity();
+        m.cols[3] = vec4!(v3 t, 1);
+        m
+    }
+
+    pub fn rotation(axis: &Vec3, angle: Real) -> Self {
+        Self::from_mat3(&Mat3::rotation(axis, angle))
+    }
+
+    // World to camera space for a camera at eye looking at target, with the
+    // camera looking down its -z axis like gluLookAt
+    pub fn look_at(eye: &Vec3, target: &Vec3, up: &Vec3) -> Self {
+        let basis = Mat3::look_at(&(*target - *eye), up);
+        let [right, up, forward] = basis.cols;
+        let rotation = Self::from_mat3(&Mat3::new(&right, &up, &-forward).transpose());
+        rotation * Self::translation(&-*eye)
+    }
+
+    // OpenGL style projection into the [-1, 1] clip cube, fov_y in radians
+    pub fn perspective(fov_y: Real, aspect: Real, near: Real, far: Real) -> Self {
+        let f = 1.0 / (fov_y / 2.0).tan();
+        Self::new(
+            &vec4!(f / aspect, 0, 0, 0),
+            &vec4!(0, f, 0, 0),
+            &vec4!(0, 0, (far + near) / (near - far), -1),
+            &vec4!(0, 0, 2.0 * far * near / (near - far), 0)
+        )
+    }
+
+    // Applies the full transform to a point, including the perspective divide
+    pub fn transform_point(&self, p: &Vec3) -> Vec3 {
+        let q = *self * vec4!(v3 p, 1);
+        q.xyz() / q.w
+    }
+
+    // Applies only the linear part, leaving out translation
+    pub fn transform_vector(&self, v: &Vec3) -> Vec3 {
+        (*self * vec4!(v3 v, 0)).xyz()
+    }
+
+    pub fn transpose(&self) -> Self {
+        let [a, b, c, d] = self.cols;
+        Self::new(
+            &vec4!(a.x, b.x, c.x, d.x),
+            &vec4!(a.y, b.y, c.y, d.y),
+            &vec4!(a.z, b.z, c.z, d.z),
+            &vec4!(a.w, b.w, c.w, d.w)
+        )
+    }
+
+    fn entries(&self) -> [[Real; 4]; 4] {
+        self.cols.map(|c| [c.x, c.y, c.z, c.w])
+    }
+
+    // Signed determinant of what's left after removing a row and a column
+    fn cofactor(m: &[[Real; 4]; 4], row: usize, col: usize) -> Real {
+        let mut cols = (0..4).filter(|&c| c != col).map(|c| {
+            let mut rows = (0..4).filter(|&r| r != row).map(|r| m[c][r]);
+            vec3!(rows.next().unwrap(), rows.next().unwrap(), rows.next().unwrap())
+        });
+        let minor = Mat3::new(&cols.next().unwrap(), &cols.next().unwrap(), &cols.next().unwrap());
+        if (row + col) % 2 == 0 { minor.determinant() } else { -minor.determinant() }
+    }
+
+    pub fn determinant(&self) -> Real {
+        let m = self.entries();
+        (0..4).map(|row| m[0][row] * Self::cofactor(&m, row, 0)).sum()
+    }
+
+    // None when the matrix is singular
+    pub fn inverse(&self) -> Option<Self> {
+        let det = self.determinant();
+        if det == 0.0 {
+            return None;
+        }
+        let m = self.entries();
+        // the transposed cofactors over the determinant
+        let col = |c| vec4!(
+            Self::cofactor(&m, c, 0),
+            Self::cofactor(&m, c, 1),
+            Self::cofactor(&m, c, 2),
+            Self::cofactor(&m, c, 3)
+        ) / det;
+        Some(Self::new(&col(0), &col(1), &col(2), &col(3)))
+    }
+}
+
+impl_mat_ops!(Mat4, Vec4; [0 => x, 1 => y, 2 => z, 3 => w]);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::linalg::approx::EPSILON;
+
+    fn assert_close(a: &Mat4, b: &Mat4) {
+        for (x, y) in a.cols.iter().zip(b.cols.iter()) {
+            assert!((*x - *y).len() < EPSILON, "{:?} != {:?}", a, b);
+        }
+    }
+
+    fn messy() -> Mat4 {
+        Mat4::from_rows(
+            &vec4!(2, -1, 0, 3),
+            &vec4!(1, 4, 2, -2),
+            &vec4!(0, 1, 5, 1),
+            &vec4!(3, 0, -1, 2)
+        )
+    }
+
+    #[test]
+    fn determinant() {
+        assert_eq!(Mat4::identity().determinant(), 1.0);
+        assert_eq!(Mat4::scale(&vec3!(2, 3, 4)).determinant(), 24.0);
+        // by cofactor expansion
+        assert!((messy().determinant() - -28.0).abs() < EPSILON);
+    }
+
+    #[test]
+    fn inverse() {
+        let m = messy();
+        assert_close(&(m * m.inverse().unwrap()), &Mat4::identity());
+        assert_close(&(m.inverse().unwrap() * m), &Mat4::identity());
+        assert_eq!(Mat4::scale(&vec3!(0, 1, 1)).inverse(), None);
+    }
+
+    #[test]
+    fn points_and_vectors() {
+        let m = Mat4::translation(&vec3!(1, 2, 3)) * Mat4::scale(&vec3!(2));
+        assert_eq!(m.transform_point(&vec3!(1, 1, 1)), vec3!(3, 4, 5));
+        assert_eq!(m.transform_vector(&vec3!(1, 1, 1)), vec3!(2, 2, 2));
+    }
+
+    #[test]
+    fn look_at() {
+        let view = Mat4::look_at(&vec3!(0, 0, 5), &vec3!(0), &vec3!(0, 1, 0));
+        // the target ends up straight ahead, down -z
+        assert!((view.transform_point(&vec3!(0)) - vec3!(0, 0, -5)).len() < EPSILON);
+        assert!((view.transform_point(&vec3!(1, 0, 5)) - vec3!(1, 0, 0)).len() < EPSILON);
+    }
+
+    #[test]
+    fn perspective() {
+        let proj = Mat4::perspective(crate::linalg::consts::FRAC_PI_2, 2.0, 1.0, 10.0);
+        // near and far planes map to the ends of the clip cube
+        assert!((proj.transform_point(&vec3!(0, 0, -1)).z - -1.0).abs() < EPSILON);
+        assert!((proj.transform_point(&vec3!(0, 0, -10)).z - 1.0).abs() < EPSILON);
+        // a 90 degree fov puts y = -z at the top edge
+        assert!((proj.transform_point(&vec3!(0, 3, -3)).y - 1.0).abs() < EPSILON);
+        assert!((proj.transform_point(&vec3!(6, 0, -3)).x - 1.0).abs() < EPSILON);
+    }
+
+    generate_op_test!(add; Mat4::identity(), +, Mat4::translation(&vec3!(1, 2, 3)) => Mat4::new(&vec4!(2, 0, 0, 0), &vec4!(0, 2, 0, 0), &vec4!(0, 0, 2, 0), &vec4!(1, 2, 3, 2)));
+    generate_op_test!(mul_vec; Mat4::translation(&vec3!(1, 2, 3)), *, vec4!(0, 0, 0, 1) => vec4!(1, 2, 3, 1));
+    generate_op_test!((assign) mul_assign_float; Mat4::translation(&vec3!(1, 2, 3)), *=, 2.0 => Mat4::new(&vec4!(2, 0, 0, 0), &vec4!(0, 2, 0, 0), &vec4!(0, 0, 2, 0), &vec4!(2, 4, 6, 2)));
+}
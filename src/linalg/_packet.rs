@@ -0,0 +1,216 @@
+use std::convert::TryInto;
+use std::ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Neg, Sub, SubAssign, Rem};
+use crate::linalg::{Real, Scalar, Vec3};
+
+// How many rays march together. Eight with AVX, which keeps more of them
+// in flight, and four otherwise. Arithmetic runs four lanes at a time
+#[cfg(not(target_feature = "avx"))]
+pub const LANES: usize = 4;
+#[cfg(target_feature = "avx")]
+pub const LANES: usize = 8;
+
+// One number per ray in a packet, so Vec3<Packet> holds a bundle of points
+// as structure of arrays. Arithmetic is lane by lane, which lets anything
+// written against Scalar evaluate all of them in one pass
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Packet(pub [Real; LANES]);
+
+impl Packet {
+    pub fn splat(x: Real) -> Self {
+        Self([x; LANES])
+    }
+
+    pub fn from_fn<F: FnMut(usize) -> Real>(mut f: F) -> Self {
+        let mut lanes = [0.0; LANES];
+        for (i, lane) in lanes.iter_mut().enumerate() {
+            *lane = f(i);
+        }
+        Self(lanes)
+    }
+
+    pub fn map<F: Fn(Real) -> Real>(self, f: F) -> Self {
+        Self::from_fn(|i| f(self.0[i]))
+    }
+
+    // Runs one of Real's four lane kernels over the packet, so the simd
+    // feature speeds this up too
+    #[inline]
+    fn kernel(self, rhs: Self, f: fn([Real; 4], [Real; 4]) -> [Real; 4]) -> Self {
+        let mut out = [0.0; LANES];
+        for i in (0..LANES).step_by(4) {
+            let r = f(self.0[i..i + 4].try_into().unwrap(), rhs.0[i..i + 4].try_into().unwrap());
+            out[i..i + 4].copy_from_slice(&r);
+        }
+        Self(out)
+    }
+}
+
+impl Vec3<Packet> {
+    pub fn gather(points: &[Vec3; LANES]) -> Self {
+        Vec3::new(
+            Packet::from_fn(|i| points[i].x),
+            Packet::from_fn(|i| points[i].y),
+            Packet::from_fn(|i| points[i].z)
+        )
+    }
+
+    pub fn extract(&self, lane: usize) -> Vec3 {
+        Vec3::new(self.x.0[lane], self.y.0[lane], self.z.0[lane])
+    }
+}
+
+macro_rules! impl_packet_ops {
+    ($(($op_trait:ident, $op_fn:ident, $op_assign_trait:ident, $op_assign_fn:ident => $kernel:ident)),+) => {
+        $(
+            impl $op_trait for Packet {
+                type Output = Self;
+                #[inline]
+                fn $op_fn(self, rhs: Self) -> Self {
+                    self.kernel(rhs, Real::$kernel)
+                }
+            }
+
+            impl $op_assign_trait for Packet {
+                fn $op_assign_fn(&mut self, rhs: Self) {
+                    *self = self.kernel(rhs, Real::$kernel);
+                }
+            }
+        )+
+    };
+}
+
+impl_packet_ops!(
+    (Add, add, AddAssign, add_assign => add4),
+    (Sub, sub, SubAssign, sub_assign => sub4),
+    (Mul, mul, MulAssign, mul_assign => mul4),
+    (Div, div, DivAssign, div_assign => div4)
+);
+
+impl Rem for Packet {
+    type Output = Self;
+    fn rem(self, rhs: Self) -> Self {
+        Self::from_fn(|i| self.0[i] % rhs.0[i])
+    }
+}
+
+impl Neg for Packet {
+    type Output = Self;
+    fn neg(self) -> Self {
+        self.map(|x| -x)
+    }
+}
+
+impl Scalar for Packet {
+    fn from_f64(x: f64) -> Self {
+        Self::splat(Real::from_f64(x))
+    }
+
+    fn select_lt(self, other: Self, if_less: Self, otherwise: Self) -> Self {
+        Self::from_fn(|i| if self.0[i] < other.0[i] { if_less.0[i] } else { otherwise.0[i] })
+    }
+
+    fn sqrt(self) -> Self {
+        self.map(Real::sqrt)
+    }
+
+    fn abs(self) -> Self {
+        self.map(Real::abs)
+    }
+
+    fn min(self, other: Self) -> Self {
+        Self::from_fn(|i| self.0[i].min(other.0[i]))
+    }
+
+    fn max(self, other: Self) -> Self {
+        Self::from_fn(|i| self.0[i].max(other.0[i]))
+    }
+
+    fn floor(self) -> Self {
+        self.map(Real::floor)
+    }
+
+    fn signum(self) -> Self {
+        self.map(Real::signum)
+    }
+
+    fn sin(self) -> Self {
+        self.map(Real::sin)
+    }
+
+    fn cos(self) -> Self {
+        self.map(Real::cos)
+    }
+
+    fn acos(self) -> Self {
+        self.map(Real::acos)
+    }
+
+    fn atan2(self, x: Self) -> Self {
+        Self::from_fn(|i| self.0[i].atan2(x.0[i]))
+    }
+
+    fn powf(self, n: Self) -> Self {
+        Self::from_fn(|i| self.0[i].powf(n.0[i]))
+    }
+
+    fn ln(self) -> Self {
+        self.map(Real::ln)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn points() -> [Vec3; LANES] {
+        let mut points = [vec3!(0); LANES];
+        for (i, p) in points.iter_mut().enumerate() {
+            *p = vec3!(i, 1.5 - i as Real, 0.25 * i as Real);
+        }
+        points
+    }
+
+    #[test]
+    fn matches_each_lane() {
+        let points = points();
+        let packet = Vec3::gather(&points);
+        let len = packet.len();
+        let normalized = (packet * Packet::splat(2.0) - packet).normalized();
+        for (i, p) in points.iter().enumerate() {
+            assert_eq!(packet.extract(i), *p);
+            assert_approx_eq!(len.0[i], p.len());
+            assert_approx_eq!(normalized.extract(i), p.normalized());
+        }
+    }
+
+    #[test]
+    fn branches_per_lane() {
+        // -1, 0, 1, 2 and on, so the lanes fall on both sides of every edge
+        let x = Packet::from_fn(|i| i as Real - 1.0);
+        let v = Vec3::new(x, -x, Packet::splat(0.5));
+        let (step, sign) = (v.step(Packet::splat(0.5)), v.sign());
+        for i in 0..LANES {
+            let lane = v.extract(i);
+            assert_eq!(step.extract(i), lane.step(0.5));
+            assert_eq!(sign.extract(i), lane.sign());
+        }
+        assert_eq!(step.x.0[..4], [0.0, 0.0, 1.0, 1.0]);
+        assert_eq!(sign.x.0[..4], [-1.0, 0.0, 1.0, 1.0]);
+    }
+
+    #[test]
+    fn refracts_per_lane() {
+        // steeper rays get through, grazing ones are totally reflected
+        let n = vec3!(0, 1, 0);
+        let p = Vec3::gather(&points());
+        let dirs = Vec3::new(p.x, -p.y.abs(), p.z).normalized();
+        let refracted = dirs.refract(&Vec3::new(Packet::splat(n.x), Packet::splat(n.y), Packet::splat(n.z)), Packet::splat(1.5));
+        let mut reflected = 0;
+        for i in 0..LANES {
+            let expected = dirs.extract(i).refract(&n, 1.5);
+            assert_approx_eq!(refracted.extract(i), expected);
+            reflected += (expected == vec3!(0)) as usize;
+        }
+        assert!(reflected > 0 && reflected < LANES);
+    }
+}
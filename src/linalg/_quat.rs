@@ -0,0 +1,236 @@
+use std::ops::{Mul, MulAssign, Neg};
+use crate::linalg::{Real, Scalar, Mat3, Vec3, Vec4};
+
+// Rotation as a unit quaternion x i + y j + z k + w.
+// Rotations follow the same right handed convention as Mat3
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Quat {
+    pub x: Real,
+    pub y: Real,
+    pub z: Real,
+    pub w: Real
+}
+
+impl Quat {
+    pub fn new(x: Real, y: Real, z: Real, w: Real) -> Self {
+        Self {x, y, z, w}
+    }
+
+    pub fn identity() -> Self {
+        Self::new(0.0, 0.0, 0.0, 1.0)
+    }
+
+    pub fn from_axis_angle(axis: &Vec3, angle: Real) -> Self {
+        let (s, c) = (angle / 2.0).sin_cos();
+        let a = axis.normalized() * s;
+        Self::new(a.x, a.y, a.z, c)
+    }
+
+    // Rotates around x, then y, then z, the same as
+    // Mat3::rotation_z(z) * Mat3::rotation_y(y) * Mat3::rotation_x(x)
+    pub fn from_euler(x: Real, y: Real, z: Real) -> Self {
+        Self::from_axis_angle(&vec3!(0, 0, 1), z)
+            * Self::from_axis_angle(&vec3!(0, 1, 0), y)
+            * Self::from_axis_angle(&vec3!(1, 0, 0), x)
+    }
+
+    // Only meaningful for rotation matrices
+    pub fn from_mat3(m: &Mat3) -> Self {
+        let [a, b, c] = m.cols;
+        let trace = a.x + b.y + c.z;
+        // pick the largest component to divide by, to stay stable near 180 degrees
+        let q = if trace > 0.0 {
+            let s = (trace + 1.0).sqrt() * 2.0;
+            Self::new((b.z - c.y) / s, (c.x - a.z) / s, (a.y - b.x) / s, s / 4.0)
+        } else if a.x > b.y && a.x > c.z {
+            let s = (1.0 + a.x - b.y - c.z).sqrt() * 2.0;
+            Self::new(s / 4.0, (b.x + a.y) / s, (c.x + a.z) / s, (b.z - c.y) / s)
+        } else if b.y > c.z {
+            let s = (1.0 + b.y - a.x - c.z).sqrt() * 2.0;
+            Self::new((b.x + a.y) / s, s / 4.0, (c.y + b.z) / s, (c.x - a.z) / s)
+        } else {
+            let s = (1.0 + c.z - a.x - b.y).sqrt() * 2.0;
+            Self::new((c.x + a.z) / s, (c.y + b.z) / s, s / 4.0, (a.y - b.x) / s)
+        };
+        q.normalized()
+    }
+
+    pub fn to_mat3(&self) -> Mat3 {
+        Mat3::new(
+            &self.rotate(&vec3!(1, 0, 0)),
+            &self.rotate(&vec3!(0, 1, 0)),
+            &self.rotate(&vec3!(0, 0, 1))
+        )
+    }
+
+    // The axis is arbitrary for the identity
+    pub fn to_axis_angle(&self) -> (Vec3, Real) {
+        let q = if self.w < 0.0 { -*self } else { *self };
+        let s = q.xyz().len();
+        if s < 1e-12 {
+            return (vec3!(1, 0, 0), 0.0);
+        }
+        (q.xyz() / s, 2.0 * s.atan2(q.w))
+    }
+
+    pub fn xyz(&self) -> Vec3 {
+        vec3!(self.x, self.y, self.z)
+    }
+
+    pub fn xyzw(&self) -> Vec4 {
+        vec4!(self.x, self.y, self.z, self.w)
+    }
+
+    pub fn len(&self) -> Real {
+        self.xyzw().len()
+    }
+
+    pub fn normalized(&self) -> Self {
+        let v = self.xyzw().normalized();
+        Self::new(v.x, v.y, v.z, v.w)
+    }
+
+    pub fn dot(&self, other: &Self) -> Real {
+        self.xyzw().dot(&other.xyzw())
+    }
+
+    pub fn conjugate(&self) -> Self {
+        Self::new(-self.x, -self.y, -self.z, self.w)
+    }
+
+    // Same as the conjugate for unit quaternions
+    pub fn inverse(&self) -> Self {
+        let c = self.conjugate().xyzw() / self.dot(self);
+        Self::new(c.x, c.y, c.z, c.w)
+    }
+
+    // q v q*, expanded so it skips building the middle product.
+    // Works on any scalar, so it can carry derivatives along
+    pub fn rotate<S: Scalar>(&self, v: &Vec3<S>) -> Vec3<S> {
+        let u = Vec3::new(S::from_real(self.x), S::from_real(self.y), S::from_real(self.z));
+        let t = u.cross(v) * S::from_real(2.0);
+        *v + t * S::from_real(self.w) + u.cross(&t)
+    }
+
+    // Constant speed interpolation along the shorter arc
+    pub fn slerp(&self, other: &Self, t: Real) -> Self {
+        let mut cos = self.dot(other);
+        let mut end = other.xyzw();
+        if cos < 0.0 {
+            cos = -cos;
+            end = -end;
+        }
+        let start = self.xyzw();
+        let v = if cos > 0.9995 {
+            // nearly parallel, where lerping is just as good and doesn't divide by 0
+            start + (end - start) * t
+        } else {
+            let angle = cos.acos();
+            (start * ((1.0 - t) * angle).sin() + end * (t * angle).sin()) / angle.sin()
+        };
+        Self::new(v.x, v.y, v.z, v.w).normalized()
+    }
+}
+
+impl Neg for Quat {
+    type Output = Self;
+    fn neg(self) -> Self {
+        Self::new(-self.x, -self.y, -self.z, -self.w)
+    }
+}
+
+// Hamilton product, rhs is applied first when rotating
+impl Mul for Quat {
+    type Output = Self;
+    fn mul(self, rhs: Self) -> Self {
+        let (a, b) = (self.xyz(), rhs.xyz());
+        let v = b * self.w + a * rhs.w + a.cross(&b);
+        Self::new(v.x, v.y, v.z, self.w * rhs.w - a.dot(&b))
+    }
+}
+
+impl MulAssign for Quat {
+    fn mul_assign(&mut self, rhs: Self) {
+        *self = *self * rhs;
+    }
+}
+
+impl Mul<Vec3> for Quat {
+    type Output = Vec3;
+    fn mul(self, rhs: Vec3) -> Vec3 {
+        self.rotate(&rhs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::linalg::approx::EPSILON;
+    use crate::linalg::consts::{FRAC_PI_2, PI};
+
+    fn close(a: &Vec3, b: &Vec3) -> bool {
+        (*a - *b).len() < EPSILON
+    }
+
+    fn assert_same_rotation(a: &Quat, b: &Quat) {
+        // q and -q are the same rotation
+        assert!((a.dot(b).abs() - 1.0).abs() < EPSILON, "{:?} != {:?}", a, b);
+    }
+
+    #[test]
+    fn rotate_matches_mat3() {
+        let axis = vec3!(1, -2, 0.5);
+        let q = Quat::from_axis_angle(&axis, 0.7);
+        let m = Mat3::rotation(&axis, 0.7);
+        let v = vec3!(0.3, 2, -1);
+        assert!(close(&q.rotate(&v), &(m * v)));
+        for (a, b) in q.to_mat3().cols.iter().zip(m.cols.iter()) {
+            assert!(close(a, b));
+        }
+        assert_same_rotation(&Quat::from_mat3(&m), &q);
+    }
+
+    #[test]
+    fn from_mat3_near_half_turns() {
+        for axis in [vec3!(1, 0, 0), vec3!(0, 1, 0), vec3!(0, 0, 1), vec3!(1, 1, 0)].iter() {
+            let q = Quat::from_axis_angle(axis, PI);
+            assert_same_rotation(&Quat::from_mat3(&q.to_mat3()), &q);
+        }
+    }
+
+    #[test]
+    fn euler() {
+        let q = Quat::from_euler(0.1, 0.2, 0.3);
+        let m = Mat3::rotation_z(0.3) * Mat3::rotation_y(0.2) * Mat3::rotation_x(0.1);
+        let v = vec3!(1, 2, 3);
+        assert!(close(&(q * v), &(m * v)));
+    }
+
+    #[test]
+    fn product_composes_rotations() {
+        let a = Quat::from_axis_angle(&vec3!(0, 0, 1), FRAC_PI_2);
+        let b = Quat::from_axis_angle(&vec3!(1, 0, 0), FRAC_PI_2);
+        let v = vec3!(0, 1, 0);
+        assert!(close(&((a * b) * v), &(a * (b * v))));
+        assert!(close(&((a * a.inverse()) * v), &v));
+    }
+
+    #[test]
+    fn axis_angle_roundtrip() {
+        let (axis, angle) = Quat::from_axis_angle(&vec3!(0, 3, 4), 2.5).to_axis_angle();
+        assert!(close(&axis, &vec3!(0, 0.6, 0.8)));
+        assert!((angle - 2.5).abs() < EPSILON);
+        assert_eq!(Quat::identity().to_axis_angle().1, 0.0);
+    }
+
+    #[test]
+    fn slerp() {
+        let a = Quat::identity();
+        let b = Quat::from_axis_angle(&vec3!(0, 1, 0), FRAC_PI_2);
+        assert_same_rotation(&a.slerp(&b, 0.0), &a);
+        assert_same_rotation(&a.slerp(&b, 1.0), &b);
+        assert_same_rotation(&a.slerp(&b, 0.5), &Quat::from_axis_angle(&vec3!(0, 1, 0), FRAC_PI_2 / 2.0));
+        // takes the short way even when the signs disagree
+        assert_same_rotation(&a.slerp(&-b, 0.5), &Quat::from_axis_angle(&vec3!(0, 1, 0), FRAC_PI_2 / 2.0));
+    }
+}
@@ -1,113 +1,99 @@
-use std::ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Neg, Sub, SubAssign, Rem};
+use std::fmt;
+use std::iter::{Product, Sum};
+use std::ops::{Add, AddAssign, Div, DivAssign, Index, IndexMut, Mul, MulAssign, Neg, Sub, SubAssign, Rem};
+use std::str::FromStr;
+use crate::linalg::{Real, Scalar};
+use crate::linalg::parse::{parse_components, ParseVecError};
 use crate::linalg::Vec3;
 use crate::linalg::Vec4;
 
 #[derive(Debug, Clone, Copy, PartialEq)]
-pub struct Vec2 {
-    pub x: f64,
-    pub y: f64
+pub struct Vec2<T = Real> {
+    pub x: T,
+    pub y: T
 }
 
-impl Vec2 {
-    pub fn new(x: f64, y: f64) -> Self {
+impl<T: Scalar> Vec2<T> {
+    pub fn new(x: T, y: T) -> Self {
         Self {x, y}
     }
 
-    pub fn len(&self) -> f64 {
+    pub fn len(&self) -> T {
         (self.x * self.x + self.y * self.y).sqrt()
     }
 
     pub fn normalized(&self) -> Self {
-        (*self / self.len())
+        *self / self.len()
     }
 
-    pub fn dot(&self, other: &Self) -> f64 {
+    pub fn dot(&self, other: &Self) -> T {
         self.x * other.x + self.y * other.y
     }
 
 
     pub fn abs(&self) -> Self {
-        vec2!(
+        Self::new(
             self.x.abs(),
             self.y.abs()
         )
     }
 
-    pub fn min(&self, num: f64) -> Self {
-        vec2!(
+    pub fn min(&self, num: T) -> Self {
+        Self::new(
             self.x.min(num),
             self.y.min(num)
         )
     }
 
-    pub fn max(&self, num: f64) -> Self {
-        vec2!(
+    pub fn max(&self, num: T) -> Self {
+        Self::new(
             self.x.max(num),
             self.y.max(num)
         )
     }
 
-    pub fn clamp(&self, lower: f64, upper: f64) -> Self {
+    pub fn clamp(&self, lower: T, upper: T) -> Self {
         self.max(lower).min(upper)
     }
 
-    pub fn modulo(&self, modulus: f64) -> Self {
+    pub fn modulo(&self, modulus: T) -> Self {
         ((*self % modulus) + modulus) % modulus
     }
 }
 
+impl Vec2 {
+    // The same vector as constants of another Scalar type
+    pub fn lift<S: Scalar>(&self) -> Vec2<S> {
+        Vec2::new(S::from_real(self.x), S::from_real(self.y))
+    }
+}
+
 impl_vec_ops!(Vec2; [x, y] (Add, add, AddAssign, add_assign => +));
 impl_vec_ops!(Vec2; [x, y] (Sub, sub, SubAssign, sub_assign => -));
 impl_vec_ops!(Vec2; [x, y] (Mul, mul, MulAssign, mul_assign => *));
 impl_vec_ops!(Vec2; [x, y] (Div, div, DivAssign, div_assign => /));
+impl_vec_functions!(Vec2; [x, y]);
+impl_vec_conversions!(Vec2, 2; (T, T); [0 => x, 1 => y]);
 
-impl Neg for Vec2 {
+impl<T: Scalar> Neg for Vec2<T> {
     type Output = Self;
     fn neg(self) -> Self {
-        vec2!(-self.x, -self.y)
+        Self::new(-self.x, -self.y)
     }
 }
 
-impl Rem<f64> for Vec2 {
+impl<T: Scalar> Rem<T> for Vec2<T> {
     type Output = Self;
-    fn rem(self, rhs: f64) -> Self {
-        vec2!(
+    fn rem(self, rhs: T) -> Self {
+        Self::new(
             self.x % rhs,
             self.y % rhs
         )
     }
 }
 
-// Does this look like a lot of repeated lines?
-// You should see Vec3...
-swizzle!((Vec2) xx, x, x);
-swizzle!((Vec2) xy, x, y);
-swizzle!((Vec2) yx, y, x);
-swizzle!((Vec2) yy, y, y);
-swizzle!((Vec2) xxx, x, x, x);
-swizzle!((Vec2) xxy, x, x, y);
-swizzle!((Vec2) xyx, x, y, x);
-swizzle!((Vec2) xyy, x, y, y);
-swizzle!((Vec2) yxx, y, x, x);
-swizzle!((Vec2) yxy, y, x, y);
-swizzle!((Vec2) yyx, y, y, x);
-swizzle!((Vec2) yyy, y, y, y);
-swizzle!((Vec2) xxxx, x, x, x, x);
-swizzle!((Vec2) xxxy, x, x, x, y);
-swizzle!((Vec2) xxyx, x, x, y, x);
-swizzle!((Vec2) xxyy, x, x, y, y);
-swizzle!((Vec2) xyxx, x, y, x, x);
-swizzle!((Vec2) xyxy, x, y, x, y);
-swizzle!((Vec2) xyyx, x, y, y, x);
-swizzle!((Vec2) xyyy, x, y, y, y);
-swizzle!((Vec2) yxxx, y, x, x, x);
-swizzle!((Vec2) yxxy, y, x, x, y);
-swizzle!((Vec2) yxyx, y, x, y, x);
-swizzle!((Vec2) yxyy, y, x, y, y);
-swizzle!((Vec2) yyxx, y, y, x, x);
-swizzle!((Vec2) yyxy, y, y, x, y);
-swizzle!((Vec2) yyyx, y, y, y, x);
-swizzle!((Vec2) yyyy, y, y, y, y);
+// Reads like v.yx() and v.st(), writes like v.set_yx(&u)
+swizzles!(Vec2; [x y], [r g], [s t]);
 
 
 #[cfg(test)]
@@ -147,7 +133,7 @@ mod tests {
     #[test]
     fn normalize() {
         let vec = vec2!(1, 2);
-        assert_eq!(vec.normalized().len(), 1.0);
+        assert_approx_eq!(vec.normalized().len(), 1.0);
     }
 
     #[test]
@@ -183,4 +169,14 @@ mod tests {
         let vec = vec2!(1, -2);
         assert_eq!(-vec, vec2!(-1, 2));
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn every_swizzle() {
+        let v = vec2!(1, 2);
+        let (mut gets, mut sets) = (0, 0);
+        for_each_swizzle!(check_swizzle (v, gets, sets); [(x x) (y y)]);
+        for_each_swizzle!(check_swizzle (v, gets, sets); [(r x) (g y)]);
+        for_each_swizzle!(check_swizzle (v, gets, sets); [(s x) (t y)]);
+        assert_eq!((gets, sets), (28 * 3, 2 * 3));
+    }
+}
@@ -1,33 +1,43 @@
-use std::ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Neg, Sub, SubAssign, Rem};
+use std::fmt;
+use std::iter::{Product, Sum};
+use std::ops::{Add, AddAssign, Div, DivAssign, Index, IndexMut, Mul, MulAssign, Neg, Sub, SubAssign, Rem};
+use std::str::FromStr;
+use crate::linalg::{Real, Scalar};
+use crate::linalg::parse::{parse_components, ParseVecError};
 use crate::linalg::Vec2;
 use crate::linalg::Vec4;
 
 #[derive(Debug, Clone, Copy, PartialEq)]
-pub struct Vec3 {
-    pub x: f64,
-    pub y: f64,
-    pub z: f64
+pub struct Vec3<T = Real> {
+    pub x: T,
+    pub y: T,
+    pub z: T
 }
 
-impl Vec3 {
-    pub fn new(x: f64, y: f64, z: f64) -> Self {
+impl<T: Scalar> Vec3<T> {
+    pub fn new(x: T, y: T, z: T) -> Self {
         Self {x, y, z}
     }
 
-    pub fn len(&self) -> f64 {
-        (self.x * self.x + self.y * self.y + self.z * self.z).sqrt()
+    pub fn len(&self) -> T {
+        self.dot(self).sqrt()
     }
 
     pub fn normalized(&self) -> Self {
-        (*self / self.len())
+        *self / self.len()
     }
 
-    pub fn dot(&self, other: &Self) -> f64 {
-        self.x * other.x + self.y * other.y + self.z * other.z
+    pub fn dot(&self, other: &Self) -> T {
+        if T::LANE_KERNELS {
+            let zero = T::from_real(0.0);
+            T::dot4(self.lanes(zero), other.lanes(zero))
+        } else {
+            self.x * other.x + self.y * other.y + self.z * other.z
+        }
     }
 
     pub fn cross(&self, other: &Self) -> Self {
-        vec3!(
+        Self::new(
             self.y * other.z - self.z * other.y,
             self.z * other.x - self.x * other.z,
             self.x * other.y - self.y * other.x
@@ -35,54 +45,64 @@ impl Vec3 {
     }
 
     pub fn abs(&self) -> Self {
-        vec3!(
+        Self::new(
             self.x.abs(),
             self.y.abs(),
             self.z.abs()
         )
     }
 
-    pub fn min(&self, num: f64) -> Self {
-        vec3!(
+    pub fn min(&self, num: T) -> Self {
+        Self::new(
             self.x.min(num),
             self.y.min(num),
             self.z.min(num)
         )
     }
 
-    pub fn max(&self, num: f64) -> Self {
-        vec3!(
+    pub fn max(&self, num: T) -> Self {
+        Self::new(
             self.x.max(num),
             self.y.max(num),
             self.z.max(num)
         )
     }
 
-    pub fn clamp(&self, lower: f64, upper: f64) -> Self {
+    pub fn clamp(&self, lower: T, upper: T) -> Self {
         self.max(lower).min(upper)
     }
 
-    pub fn modulo(&self, modulus: f64) -> Self {
+    pub fn modulo(&self, modulus: T) -> Self {
         ((*self % modulus) + modulus) % modulus
     }
 }
 
-impl_vec_ops!(Vec3; [x, y, z] (Add, add, AddAssign, add_assign => +));
-impl_vec_ops!(Vec3; [x, y, z] (Sub, sub, SubAssign, sub_assign => -));
-impl_vec_ops!(Vec3; [x, y, z] (Mul, mul, MulAssign, mul_assign => *));
-impl_vec_ops!(Vec3; [x, y, z] (Div, div, DivAssign, div_assign => /));
+impl Vec3 {
+    // The same vector as constants of another Scalar type
+    pub fn lift<S: Scalar>(&self) -> Vec3<S> {
+        Vec3::new(S::from_real(self.x), S::from_real(self.y), S::from_real(self.z))
+    }
+}
+
+impl_vec_lanes!(Vec3; [0 => x, 1 => y, 2 => z]);
+impl_vec_ops!(lanes Vec3; [x, y, z] (Add, add, AddAssign, add_assign => + add4));
+impl_vec_ops!(lanes Vec3; [x, y, z] (Sub, sub, SubAssign, sub_assign => - sub4));
+impl_vec_ops!(lanes Vec3; [x, y, z] (Mul, mul, MulAssign, mul_assign => * mul4));
+impl_vec_ops!(lanes Vec3; [x, y, z] (Div, div, DivAssign, div_assign => / div4));
+impl_vec_functions!(Vec3; [x, y, z]);
+impl_vec_conversions!(Vec3, 3; (T, T, T); [0 => x, 1 => y, 2 => z]);
 
-impl Neg for Vec3 {
+impl<T: Scalar> Neg for Vec3<T> {
     type Output = Self;
     fn neg(self) -> Self {
-        vec3!(-self.x, -self.y, -self.z)
+        Self::new(-self.x, -self.y, -self.z)
     }
 }
 
-impl Rem<f64> for Vec3 {
+impl<T: Scalar> Rem<T> for Vec3<T> {
     type Output = Self;
-    fn rem(self, rhs: f64) -> Self {
-        vec3!(
+    fn rem(self, rhs: T) -> Self {
+        Self::new(
             self.x % rhs,
             self.y % rhs,
             self.z % rhs
@@ -90,129 +110,14 @@ impl Rem<f64> for Vec3 {
     }
 }
 
-// This is but a drop in the ocean compared to Vec4
-// You have been warned
-swizzle!((Vec3) xx, x, x);
-swizzle!((Vec3) xy, x, y);
-swizzle!((Vec3) xz, x, z);
-swizzle!((Vec3) yx, y, x);
-swizzle!((Vec3) yy, y, y);
-swizzle!((Vec3) yz, y, z);
-swizzle!((Vec3) zx, z, x);
-swizzle!((Vec3) zy, z, y);
-swizzle!((Vec3) zz, z, z);
-swizzle!((Vec3) xxx, x, x, x);
-swizzle!((Vec3) xxy, x, x, y);
-swizzle!((Vec3) xxz, x, x, z);
-swizzle!((Vec3) xyx, x, y, x);
-swizzle!((Vec3) xyy, x, y, y);
-swizzle!((Vec3) xyz, x, y, z);
-swizzle!((Vec3) xzx, x, z, x);
-swizzle!((Vec3) xzy, x, z, y);
-swizzle!((Vec3) xzz, x, z, z);
-swizzle!((Vec3) yxx, y, x, x);
-swizzle!((Vec3) yxy, y, x, y);
-swizzle!((Vec3) yxz, y, x, z);
-swizzle!((Vec3) yyx, y, y, x);
-swizzle!((Vec3) yyy, y, y, y);
-swizzle!((Vec3) yyz, y, y, z);
-swizzle!((Vec3) yzx, y, z, x);
-swizzle!((Vec3) yzy, y, z, y);
-swizzle!((Vec3) yzz, y, z, z);
-swizzle!((Vec3) zxx, z, x, x);
-swizzle!((Vec3) zxy, z, x, y);
-swizzle!((Vec3) zxz, z, x, z);
-swizzle!((Vec3) zyx, z, y, x);
-swizzle!((Vec3) zyy, z, y, y);
-swizzle!((Vec3) zyz, z, y, z);
-swizzle!((Vec3) zzx, z, z, x);
-swizzle!((Vec3) zzy, z, z, y);
-swizzle!((Vec3) zzz, z, z, z);
-swizzle!((Vec3) xxxx, x, x, x, x);
-swizzle!((Vec3) xxxy, x, x, x, y);
-swizzle!((Vec3) xxxz, x, x, x, z);
-swizzle!((Vec3) xxyx, x, x, y, x);
-swizzle!((Vec3) xxyy, x, x, y, y);
-swizzle!((Vec3) xxyz, x, x, y, z);
-swizzle!((Vec3) xxzx, x, x, z, x);
-swizzle!((Vec3) xxzy, x, x, z, y);
-swizzle!((Vec3) xxzz, x, x, z, z);
-swizzle!((Vec3) xyxx, x, y, x, x);
-swizzle!((Vec3) xyxy, x, y, x, y);
-swizzle!((Vec3) xyxz, x, y, x, z);
-swizzle!((Vec3) xyyx, x, y, y, x);
-swizzle!((Vec3) xyyy, x, y, y, y);
-swizzle!((Vec3) xyyz, x, y, y, z);
-swizzle!((Vec3) xyzx, x, y, z, x);
-swizzle!((Vec3) xyzy, x, y, z, y);
-swizzle!((Vec3) xyzz, x, y, z, z);
-swizzle!((Vec3) xzxx, x, z, x, x);
-swizzle!((Vec3) xzxy, x, z, x, y);
-swizzle!((Vec3) xzxz, x, z, x, z);
-swizzle!((Vec3) xzyx, x, z, y, x);
-swizzle!((Vec3) xzyy, x, z, y, y);
-swizzle!((Vec3) xzyz, x, z, y, z);
-swizzle!((Vec3) xzzx, x, z, z, x);
-swizzle!((Vec3) xzzy, x, z, z, y);
-swizzle!((Vec3) xzzz, x, z, z, z);
-swizzle!((Vec3) yxxx, y, x, x, x);
-swizzle!((Vec3) yxxy, y, x, x, y);
-swizzle!((Vec3) yxxz, y, x, x, z);
-swizzle!((Vec3) yxyx, y, x, y, x);
-swizzle!((Vec3) yxyy, y, x, y, y);
-swizzle!((Vec3) yxyz, y, x, y, z);
-swizzle!((Vec3) yxzx, y, x, z, x);
-swizzle!((Vec3) yxzy, y, x, z, y);
-swizzle!((Vec3) yxzz, y, x, z, z);
-swizzle!((Vec3) yyxx, y, y, x, x);
-swizzle!((Vec3) yyxy, y, y, x, y);
-swizzle!((Vec3) yyxz, y, y, x, z);
-swizzle!((Vec3) yyyx, y, y, y, x);
-swizzle!((Vec3) yyyy, y, y, y, y);
-swizzle!((Vec3) yyyz, y, y, y, z);
-swizzle!((Vec3) yyzx, y, y, z, x);
-swizzle!((Vec3) yyzy, y, y, z, y);
-swizzle!((Vec3) yyzz, y, y, z, z);
-swizzle!((Vec3) yzxx, y, z, x, x);
-swizzle!((Vec3) yzxy, y, z, x, y);
-swizzle!((Vec3) yzxz, y, z, x, z);
-swizzle!((Vec3) yzyx, y, z, y, x);
-swizzle!((Vec3) yzyy, y, z, y, y);
-swizzle!((Vec3) yzyz, y, z, y, z);
-swizzle!((Vec3) yzzx, y, z, z, x);
-swizzle!((Vec3) yzzy, y, z, z, y);
-swizzle!((Vec3) yzzz, y, z, z, z);
-swizzle!((Vec3) zxxx, z, x, x, x);
-swizzle!((Vec3) zxxy, z, x, x, y);
-swizzle!((Vec3) zxxz, z, x, x, z);
-swizzle!((Vec3) zxyx, z, x, y, x);
-swizzle!((Vec3) zxyy, z, x, y, y);
-swizzle!((Vec3) zxyz, z, x, y, z);
-swizzle!((Vec3) zxzx, z, x, z, x);
-swizzle!((Vec3) zxzy, z, x, z, y);
-swizzle!((Vec3) zxzz, z, x, z, z);
-swizzle!((Vec3) zyxx, z, y, x, x);
-swizzle!((Vec3) zyxy, z, y, x, y);
-swizzle!((Vec3) zyxz, z, y, x, z);
-swizzle!((Vec3) zyyx, z, y, y, x);
-swizzle!((Vec3) zyyy, z, y, y, y);
-swizzle!((Vec3) zyyz, z, y, y, z);
-swizzle!((Vec3) zyzx, z, y, z, x);
-swizzle!((Vec3) zyzy, z, y, z, y);
-swizzle!((Vec3) zyzz, z, y, z, z);
-swizzle!((Vec3) zzxx, z, z, x, x);
-swizzle!((Vec3) zzxy, z, z, x, y);
-swizzle!((Vec3) zzxz, z, z, x, z);
-swizzle!((Vec3) zzyx, z, z, y, x);
-swizzle!((Vec3) zzyy, z, z, y, y);
-swizzle!((Vec3) zzyz, z, z, y, z);
-swizzle!((Vec3) zzzx, z, z, z, x);
-swizzle!((Vec3) zzzy, z, z, z, y);
-swizzle!((Vec3) zzzz, z, z, z, z);
+// Every combination of two to four components, with rgb and stp aliases.
+// Setters only exist where no component repeats
+swizzles!(Vec3; [x y z], [r g b], [s t p]);
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::linalg::approx::EPSILON;
 
     #[test]
     fn create_with_new() {
@@ -248,7 +153,7 @@ mod tests {
     #[test]
     fn normalize() {
         let vec = vec3!(1, 2, 1);
-        assert_eq!(vec.normalized().len(), 1.0);
+        assert_approx_eq!(vec.normalized().len(), 1.0);
     }
 
     #[test]
@@ -300,4 +205,121 @@ mod tests {
         let vec = vec3!(1.0, 2.0, 3.0);
         assert_eq!(vec3!(v2 vec.yx(), 5), vec3!(2, 1, 5));
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn component_wise() {
+        let a = vec3!(1, -2, 3);
+        let b = vec3!(0, 5, 3);
+        assert_eq!(a.min_vec(&b), vec3!(0, -2, 3));
+        assert_eq!(a.max_vec(&b), vec3!(1, 5, 3));
+        assert_eq!(a.clamp_vec(&vec3!(0), &vec3!(2)), vec3!(1, 0, 2));
+        assert_eq!(a.min_elem(), -2.0);
+        assert_eq!(a.max_elem(), 3.0);
+    }
+
+    #[test]
+    fn glsl_steps() {
+        let v = vec3!(-1.25, 0, 2.5);
+        assert_eq!(v.floor(), vec3!(-2, 0, 2));
+        assert_eq!(v.fract(), vec3!(0.75, 0, 0.5));
+        assert_eq!(v.sign(), vec3!(-1, 0, 1));
+        assert_eq!(v.step(0.0), vec3!(0, 1, 1));
+        assert_eq!(v.smoothstep(0.0, 5.0), vec3!(0, 0, 0.5));
+    }
+
+    #[test]
+    fn lerp_and_distance() {
+        let a = vec3!(0, 0, 0);
+        let b = vec3!(2, 4, 4);
+        assert_eq!(a.lerp(&b, 0.25), vec3!(0.5, 1, 1));
+        assert_eq!(a.distance(&b), 6.0);
+    }
+
+    #[test]
+    fn project_and_reject() {
+        let v = vec3!(3, 4, 5);
+        let onto = vec3!(0, 2, 0);
+        assert_eq!(v.project(&onto), vec3!(0, 4, 0));
+        assert_eq!(v.reject(&onto), vec3!(3, 0, 5));
+        assert_eq!(vec3!(1, 0, 0).angle_between(&vec3!(0, 0, 3)), std::f64::consts::FRAC_PI_2 as Real);
+    }
+
+    #[test]
+    fn reflect_and_refract() {
+        let n = vec3!(0, 1, 0);
+        let i = vec3!(1, -1, 0).normalized();
+        assert_eq!(i.reflect(&n), vec3!(i.x, -i.y, 0));
+        // no bending with matching indices
+        assert_eq!(i.refract(&n, 1.0), i);
+        // Snell's law, sin t = eta sin i
+        let t = i.refract(&n, 0.5);
+        assert!((t.x - 0.5 * i.x).abs() < EPSILON);
+        assert!((t.len() - 1.0).abs() < EPSILON);
+        // grazing rays from the dense side reflect completely
+        assert_eq!(vec3!(1, -0.1, 0).normalized().refract(&n, 1.5), vec3!(0));
+    }
+
+    #[test]
+    fn conversions() {
+        let v = vec3!(1, 2, 3);
+        assert_eq!(Vec3::from([1.0, 2.0, 3.0]), v);
+        assert_eq!(Vec3::from((1.0, 2.0, 3.0)), v);
+        let a: [Real; 3] = v.into();
+        let t: (Real, Real, Real) = v.into();
+        assert_eq!(a, [1.0, 2.0, 3.0]);
+        assert_eq!(t, (1.0, 2.0, 3.0));
+        assert_eq!(Vec3::<Real>::default(), vec3!(0));
+    }
+
+    #[test]
+    fn index_and_iter() {
+        let mut v = vec3!(1, 2, 3);
+        v[1] = 5.0;
+        assert_eq!(v[0] + v[1] + v[2], 9.0);
+        assert_eq!(v.iter().collect::<Vec<_>>(), vec![1.0, 5.0, 3.0]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn index_out_of_range() {
+        vec3!(0)[3];
+    }
+
+    #[test]
+    fn sum_and_product() {
+        let vs = vec![vec3!(1, 2, 3), vec3!(2), vec3!(-1, 0, 0.5)];
+        assert_eq!(vs.iter().sum::<Vec3>(), vec3!(2, 4, 5.5));
+        assert_eq!(vs.into_iter().product::<Vec3>(), vec3!(-2, 0, 3));
+    }
+
+    #[test]
+    fn display_and_parse() {
+        let v = vec3!(1, -2.5, 3);
+        assert_eq!(v.to_string(), "(1, -2.5, 3)");
+        assert_eq!(format!("{:.1}", v), "(1.0, -2.5, 3.0)");
+        assert_eq!(v.to_string().parse::<Vec3>(), Ok(v));
+        assert_eq!(" 1,-2.5 , 3 ".parse::<Vec3>(), Ok(v));
+        assert_eq!("[1, -2.5, 3]".parse::<Vec3>(), Ok(v));
+        assert_eq!("1, 2".parse::<Vec3>(), Err(ParseVecError::Length {expected: 3, found: 2}));
+        assert_eq!("1, x, 2".parse::<Vec3>(), Err(ParseVecError::Component("x".to_string())));
+    }
+
+    #[test]
+    fn scalar_on_the_left() {
+        let v = vec3!(1, 2, 4);
+        assert_eq!(2.0 * v, v * 2.0);
+        assert_eq!(1.0 + v, vec3!(2, 3, 5));
+        assert_eq!(1.0 - v, vec3!(0, -1, -3));
+        assert_eq!(4.0 / v, vec3!(4, 2, 1));
+    }
+
+    #[test]
+    fn every_swizzle() {
+        let v = vec3!(1, 2, 3);
+        let (mut gets, mut sets) = (0, 0);
+        for_each_swizzle!(check_swizzle (v, gets, sets); [(x x) (y y) (z z)]);
+        for_each_swizzle!(check_swizzle (v, gets, sets); [(r x) (g y) (b z)]);
+        for_each_swizzle!(check_swizzle (v, gets, sets); [(s x) (t y) (p z)]);
+        assert_eq!((gets, sets), (117 * 3, 12 * 3));
+    }
+}
@@ -0,0 +1,105 @@
+use crate::linalg::{Real, Vec2, Vec3, Vec4, Mat2, Mat3, Mat4, Quat};
+
+// Float comparisons that survive rounding. Vectors and matrices compare
+// component by component, and are equal when every component is.
+//   abs_diff_eq  |a - b| <= epsilon, for values near zero
+//   relative_eq  also passes within max_relative of the larger magnitude
+//   ulps_eq      also passes within max_ulps representable floats
+pub trait ApproxEq {
+    fn abs_diff_eq(&self, other: &Self, epsilon: Real) -> bool;
+    fn relative_eq(&self, other: &Self, epsilon: Real, max_relative: Real) -> bool;
+    fn ulps_eq(&self, other: &Self, epsilon: Real, max_ulps: u32) -> bool;
+
+    // Relative comparison with a few machine epsilons of slack, which
+    // covers the rounding of a typical chain of operations
+    fn approx_eq(&self, other: &Self) -> bool {
+        self.relative_eq(other, 4.0 * Real::EPSILON, 4.0 * Real::EPSILON)
+    }
+}
+
+impl ApproxEq for Real {
+    fn abs_diff_eq(&self, other: &Self, epsilon: Real) -> bool {
+        // catches infinities of the same sign, where the difference is NaN
+        self == other || (self - other).abs() <= epsilon
+    }
+
+    fn relative_eq(&self, other: &Self, epsilon: Real, max_relative: Real) -> bool {
+        if self.abs_diff_eq(other, epsilon) {
+            return true;
+        }
+        let largest = self.abs().max(other.abs());
+        (self - other).abs() <= largest * max_relative
+    }
+
+    fn ulps_eq(&self, other: &Self, epsilon: Real, max_ulps: u32) -> bool {
+        if self.abs_diff_eq(other, epsilon) {
+            return true;
+        }
+        if self.is_nan() || other.is_nan() || self.is_sign_negative() != other.is_sign_negative() {
+            return false;
+        }
+        // floats of the same sign are ordered like their bit patterns
+        let distance = (self.to_bits() as i64 - other.to_bits() as i64).abs();
+        distance <= max_ulps as i64
+    }
+}
+
+// A loose tolerance for tests at unit scale, a few thousand rounding
+// errors whichever precision Real is
+#[cfg(test)]
+pub(crate) const EPSILON: Real = 1e4 * Real::EPSILON;
+
+macro_rules! impl_approx_eq {
+    ($type:ty; $($part:ident $([$i:tt])?),+) => {
+        impl ApproxEq for $type {
+            fn abs_diff_eq(&self, other: &Self, epsilon: Real) -> bool {
+                $(self.$part$([$i])?.abs_diff_eq(&other.$part$([$i])?, epsilon))&&+
+            }
+
+            fn relative_eq(&self, other: &Self, epsilon: Real, max_relative: Real) -> bool {
+                $(self.$part$([$i])?.relative_eq(&other.$part$([$i])?, epsilon, max_relative))&&+
+            }
+
+            fn ulps_eq(&self, other: &Self, epsilon: Real, max_ulps: u32) -> bool {
+                $(self.$part$([$i])?.ulps_eq(&other.$part$([$i])?, epsilon, max_ulps))&&+
+            }
+        }
+    };
+}
+
+impl_approx_eq!(Vec2; x, y);
+impl_approx_eq!(Vec3; x, y, z);
+impl_approx_eq!(Vec4; x, y, z, w);
+impl_approx_eq!(Quat; x, y, z, w);
+impl_approx_eq!(Mat2; cols[0], cols[1]);
+impl_approx_eq!(Mat3; cols[0], cols[1], cols[2]);
+impl_approx_eq!(Mat4; cols[0], cols[1], cols[2], cols[3]);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scalars() {
+        assert!((0.1 + 0.2 as Real).approx_eq(&0.3));
+        assert!(!(1.0 as Real).approx_eq(&1.001));
+        assert!((1e-20 as Real).abs_diff_eq(&0.0, 1e-12));
+        assert!((1000.0 as Real).relative_eq(&1000.1, 0.0, 1e-3));
+        assert!(!(1000.0 as Real).relative_eq(&1000.1, 0.0, 1e-5));
+        assert!((1.0 as Real).ulps_eq(&(1.0 + Real::EPSILON), 0.0, 1));
+        assert!(!(1.0 as Real).ulps_eq(&(1.0 + 4.0 * Real::EPSILON), 0.0, 3));
+        assert!(!(1e-30 as Real).ulps_eq(&-1e-30, 0.0, 1000));
+        assert!(Real::INFINITY.approx_eq(&Real::INFINITY));
+        assert!(!Real::NAN.approx_eq(&Real::NAN));
+    }
+
+    #[test]
+    fn components() {
+        let v = vec3!(1, 2, 3);
+        assert!((v / 3.0 * 3.0).approx_eq(&v));
+        assert!(!v.approx_eq(&vec3!(1, 2, 3.01)));
+        assert!(v.abs_diff_eq(&vec3!(1, 2, 3.01), 0.02));
+        let m = Mat3::rotation(&v, 0.5);
+        assert!((m * m.inverse().unwrap()).abs_diff_eq(&Mat3::identity(), EPSILON));
+    }
+}
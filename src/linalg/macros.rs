@@ -1,51 +1,91 @@
+#[macro_export]
 macro_rules! vec2 {
-    ($x:expr, $y:expr) => {Vec2::new($x as f64, $y as f64)}; 
-    ($one:expr) => {Vec2::new($one as f64, $one as f64)};
+    ($x:expr, $y:expr) => {$crate::linalg::Vec2::new($x as $crate::linalg::Real, $y as $crate::linalg::Real)}; 
+    ($one:expr) => {$crate::linalg::Vec2::new($one as $crate::linalg::Real, $one as $crate::linalg::Real)};
 }
 
+#[macro_export]
 macro_rules! vec3 {
-    ($x:expr, $y:expr, $z:expr) => {Vec3::new($x as f64, $y as f64, $z as f64)};
-    ($one:expr) => {Vec3::new($one as f64, $one as f64, $one as f64)};
-    (v2 $xy:expr, $z:expr) => {Vec3::new($xy.x, $xy.y, $z as f64)};
-    ($x:expr, v2 $yz:expr) => {Vec3::new($x as f64, $yz.x, $yz.y)};
+    ($x:expr, $y:expr, $z:expr) => {$crate::linalg::Vec3::new($x as $crate::linalg::Real, $y as $crate::linalg::Real, $z as $crate::linalg::Real)};
+    ($one:expr) => {$crate::linalg::Vec3::new($one as $crate::linalg::Real, $one as $crate::linalg::Real, $one as $crate::linalg::Real)};
+    (v2 $xy:expr, $z:expr) => {$crate::linalg::Vec3::new($xy.x, $xy.y, $z as $crate::linalg::Real)};
+    ($x:expr, v2 $yz:expr) => {$crate::linalg::Vec3::new($x as $crate::linalg::Real, $yz.x, $yz.y)};
 }
 
+#[macro_export]
 macro_rules! vec4 {
-    ($x:expr, $y:expr, $z:expr, $w:expr) => {Vec4::new($x as f64, $y as f64, $z as f64, $w as f64)};
-    ($one:expr) => {Vec4::new($one as f64, $one as f64, $one as f64, $one as f64)};
-    (v2 $xy:expr, $z:expr, $w:expr) => {Vec4::new($xy.x, $xy.y, $z as f64, $w as f64)};
-    ($x:expr, v2 $yz:expr, $w:expr) => {Vec4::new($x as f64, $yz.x, $yz.y, $w as f64)};
-    ($x:expr, $y:expr, v2 $zw:expr) => {Vec4::new($x as f64, $y as f64, $zw.x, $zw.y)};
-    (v2 $xy:expr, v2 $zw:expr) => {Vec4::new($xy.x, $xy.y, $zw.x, $zw.y)};
-    (v3 $xyz:expr, $w:expr) => {Vec4::new($xyz.x, $xyz.y, $xyz.z, $w as f64)};
-    ($x:expr, v3 $yzw:expr) => {Vec4::new($x as f64, $yzw.x, $yzw.y, $yzw.z)};
+    ($x:expr, $y:expr, $z:expr, $w:expr) => {$crate::linalg::Vec4::new($x as $crate::linalg::Real, $y as $crate::linalg::Real, $z as $crate::linalg::Real, $w as $crate::linalg::Real)};
+    ($one:expr) => {$crate::linalg::Vec4::new($one as $crate::linalg::Real, $one as $crate::linalg::Real, $one as $crate::linalg::Real, $one as $crate::linalg::Real)};
+    (v2 $xy:expr, $z:expr, $w:expr) => {$crate::linalg::Vec4::new($xy.x, $xy.y, $z as $crate::linalg::Real, $w as $crate::linalg::Real)};
+    ($x:expr, v2 $yz:expr, $w:expr) => {$crate::linalg::Vec4::new($x as $crate::linalg::Real, $yz.x, $yz.y, $w as $crate::linalg::Real)};
+    ($x:expr, $y:expr, v2 $zw:expr) => {$crate::linalg::Vec4::new($x as $crate::linalg::Real, $y as $crate::linalg::Real, $zw.x, $zw.y)};
+    (v2 $xy:expr, v2 $zw:expr) => {$crate::linalg::Vec4::new($xy.x, $xy.y, $zw.x, $zw.y)};
+    (v3 $xyz:expr, $w:expr) => {$crate::linalg::Vec4::new($xyz.x, $xyz.y, $xyz.z, $w as $crate::linalg::Real)};
+    ($x:expr, v3 $yzw:expr) => {$crate::linalg::Vec4::new($x as $crate::linalg::Real, $yzw.x, $yzw.y, $yzw.z)};
 }
 
 macro_rules! impl_vec_ops {
-    ($type:ty; [$($var:ident),+]  ($op_trait:ident, $op_fn:ident, $op_assign_trait:ident, $op_assign_fn:ident => $op:tt)) => {
+    ($type:ident; [$($var:ident),+]  ($op_trait:ident, $op_fn:ident, $op_assign_trait:ident, $op_assign_fn:ident => $op:tt)) => {
 
-        impl $op_trait for $type {
+        impl<T: Scalar> $op_trait for $type<T> {
             type Output = Self;
             fn $op_fn(self, rhs: Self) -> Self {
                 Self { $($var: self.$var $op rhs.$var),+ }
             }
         }
 
-        impl $op_trait<f64> for $type {
+        impl<T: Scalar> $op_trait<T> for $type<T> {
             type Output = Self;
-            fn $op_fn(self, rhs: f64) -> Self {
+            fn $op_fn(self, rhs: T) -> Self {
                 Self { $($var: self.$var $op rhs),+ }
             }
         }
 
-        impl $op_assign_trait for $type {
+        impl_vec_ops!(@assign $type, $op_assign_trait, $op_assign_fn => $op);
+    };
+
+    // Vec3 and Vec4 go through the four lane kernels on Scalar for the
+    // types that have SIMD ones, field by field for the rest
+    (lanes $type:ident; [$($var:ident),+] ($op_trait:ident, $op_fn:ident, $op_assign_trait:ident, $op_assign_fn:ident => $op:tt $kernel:ident)) => {
+
+        impl<T: Scalar> $op_trait for $type<T> {
+            type Output = Self;
+            #[inline]
+            fn $op_fn(self, rhs: Self) -> Self {
+                if T::LANE_KERNELS {
+                    let one = T::from_real(1.0);
+                    Self::from_lanes(T::$kernel(self.lanes(one), rhs.lanes(one)))
+                } else {
+                    Self { $($var: self.$var $op rhs.$var),+ }
+                }
+            }
+        }
+
+        impl<T: Scalar> $op_trait<T> for $type<T> {
+            type Output = Self;
+            #[inline]
+            fn $op_fn(self, rhs: T) -> Self {
+                if T::LANE_KERNELS {
+                    Self::from_lanes(T::$kernel(self.lanes(T::from_real(1.0)), [rhs; 4]))
+                } else {
+                    Self { $($var: self.$var $op rhs),+ }
+                }
+            }
+        }
+
+        impl_vec_ops!(@assign $type, $op_assign_trait, $op_assign_fn => $op);
+    };
+
+    (@assign $type:ident, $op_assign_trait:ident, $op_assign_fn:ident => $op:tt) => {
+
+        impl<T: Scalar> $op_assign_trait for $type<T> {
             fn $op_assign_fn(&mut self, rhs: Self) {
                 *self = *self $op rhs;
             }
         }
 
-        impl $op_assign_trait<f64> for $type {
-            fn $op_assign_fn(&mut self, rhs: f64) {
+        impl<T: Scalar> $op_assign_trait<T> for $type<T> {
+            fn $op_assign_fn(&mut self, rhs: T) {
                 *self = *self $op rhs;
             }
         }
@@ -53,35 +93,438 @@ macro_rules! impl_vec_ops {
     };
 }
 
+// Packs a vector into four lanes and back. Vec3 fills the spare lane with
+// pad: zero for dot products, one for arithmetic so division stays finite
+macro_rules! impl_vec_lanes {
+    ($type:ident; [$($i:tt => $var:ident),+]) => {
+        impl<T: Scalar> $type<T> {
+            #[inline]
+            fn lanes(&self, pad: T) -> [T; 4] {
+                let mut lanes = [pad; 4];
+                $(lanes[$i] = self.$var;)+
+                lanes
+            }
+
+            #[inline]
+            fn from_lanes(lanes: [T; 4]) -> Self {
+                Self { $($var: lanes[$i]),+ }
+            }
+        }
+    };
+}
+
+// The GLSL built-ins that work the same on every vector size
+macro_rules! impl_vec_functions {
+    ($type:ident; [$($var:ident),+]) => {
+        impl<T: Scalar> $type<T> {
+            pub fn min_vec(&self, other: &Self) -> Self {
+                Self { $($var: self.$var.min(other.$var)),+ }
+            }
+
+            pub fn max_vec(&self, other: &Self) -> Self {
+                Self { $($var: self.$var.max(other.$var)),+ }
+            }
+
+            pub fn clamp_vec(&self, lower: &Self, upper: &Self) -> Self {
+                self.max_vec(lower).min_vec(upper)
+            }
+
+            pub fn min_elem(&self) -> T {
+                let mut m = self.x;
+                $(m = m.min(self.$var);)+
+                m
+            }
+
+            pub fn max_elem(&self) -> T {
+                let mut m = self.x;
+                $(m = m.max(self.$var);)+
+                m
+            }
+
+            pub fn floor(&self) -> Self {
+                Self { $($var: self.$var.floor()),+ }
+            }
+
+            pub fn fract(&self) -> Self {
+                *self - self.floor()
+            }
+
+            // Zero stays zero, unlike signum
+            pub fn sign(&self) -> Self {
+                let (zero, one) = (T::from_real(0.0), T::from_real(1.0));
+                Self { $($var: self.$var.signum() * zero.select_lt(self.$var.abs(), one, zero)),+ }
+            }
+
+            // 0 below the edge and 1 from it on
+            pub fn step(&self, edge: T) -> Self {
+                let (zero, one) = (T::from_real(0.0), T::from_real(1.0));
+                Self { $($var: self.$var.select_lt(edge, zero, one)),+ }
+            }
+
+            // Hermite ease from 0 at edge0 to 1 at edge1
+            pub fn smoothstep(&self, edge0: T, edge1: T) -> Self {
+                let (zero, one) = (T::from_real(0.0), T::from_real(1.0));
+                let t = ((*self - edge0) / (edge1 - edge0)).clamp(zero, one);
+                t * t * (-t * T::from_real(2.0) + T::from_real(3.0))
+            }
+
+            // GLSL calls this mix
+            pub fn lerp(&self, other: &Self, t: T) -> Self {
+                *self + (*other - *self) * t
+            }
+
+            pub fn distance(&self, other: &Self) -> T {
+                (*self - *other).len()
+            }
+
+            pub fn project(&self, onto: &Self) -> Self {
+                *onto * (self.dot(onto) / onto.dot(onto))
+            }
+
+            pub fn reject(&self, from: &Self) -> Self {
+                *self - self.project(from)
+            }
+
+            pub fn angle_between(&self, other: &Self) -> T {
+                let cos = self.dot(other) / (self.len() * other.len());
+                cos.max(T::from_real(-1.0)).min(T::from_real(1.0)).acos()
+            }
+
+            // Mirrors an incoming direction across the surface with unit normal n
+            pub fn reflect(&self, n: &Self) -> Self {
+                *self - *n * (n.dot(self) * T::from_real(2.0))
+            }
+
+            // Bends a unit direction entering a surface with unit normal n, eta being
+            // the ratio of refractive indices. Zero on total internal reflection
+            pub fn refract(&self, n: &Self, eta: T) -> Self {
+                let (zero, one) = (T::from_real(0.0), T::from_real(1.0));
+                let cos = n.dot(self);
+                let k = one - eta * eta * (one - cos * cos);
+                let t = *self * eta - *n * (eta * cos + k.max(zero).sqrt());
+                Self { $($var: k.select_lt(zero, zero, t.$var)),+ }
+            }
+        }
+    };
+}
+
+// Conversions, indexing and iteration. The tuple type is spelled out since
+// macro_rules can't repeat T once per field on its own
+macro_rules! impl_vec_conversions {
+    ($type:ident, $n:expr; $tuple:ty; [$($i:tt => $var:ident),+]) => {
+        impl<T: Scalar> $type<T> {
+            pub fn to_array(&self) -> [T; $n] {
+                [$(self.$var),+]
+            }
+
+            pub fn iter(&self) -> impl Iterator<Item = T> {
+                IntoIterator::into_iter(self.to_array())
+            }
+        }
+
+        impl<T: Scalar> Default for $type<T> {
+            fn default() -> Self {
+                Self { $($var: T::from_real(0.0)),+ }
+            }
+        }
+
+        impl<T: Scalar> From<[T; $n]> for $type<T> {
+            fn from(a: [T; $n]) -> Self {
+                Self { $($var: a[$i]),+ }
+            }
+        }
+
+        impl<T: Scalar> From<$tuple> for $type<T> {
+            fn from(t: $tuple) -> Self {
+                Self { $($var: t.$i),+ }
+            }
+        }
+
+        impl<T: Scalar> Index<usize> for $type<T> {
+            type Output = T;
+            fn index(&self, i: usize) -> &T {
+                match i {
+                    $($i => &self.$var,)+
+                    _ => panic!("index {} out of range for {}", i, stringify!($type))
+                }
+            }
+        }
+
+        impl<T: Scalar> IndexMut<usize> for $type<T> {
+            fn index_mut(&mut self, i: usize) -> &mut T {
+                match i {
+                    $($i => &mut self.$var,)+
+                    _ => panic!("index {} out of range for {}", i, stringify!($type))
+                }
+            }
+        }
+
+        impl<T: Scalar> Sum for $type<T> {
+            fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+                iter.fold(Self::default(), |a, b| a + b)
+            }
+        }
+
+        impl<'a, T: Scalar> Sum<&'a $type<T>> for $type<T> {
+            fn sum<I: Iterator<Item = &'a Self>>(iter: I) -> Self {
+                iter.copied().sum()
+            }
+        }
+
+        impl<T: Scalar> Product for $type<T> {
+            fn product<I: Iterator<Item = Self>>(iter: I) -> Self {
+                iter.fold(Self { $($var: T::from_real(1.0)),+ }, |a, b| a * b)
+            }
+        }
+
+        impl<'a, T: Scalar> Product<&'a $type<T>> for $type<T> {
+            fn product<I: Iterator<Item = &'a Self>>(iter: I) -> Self {
+                iter.copied().product()
+            }
+        }
+
+        // Formatting options like precision apply to every component
+        impl<T: Scalar + fmt::Display> fmt::Display for $type<T> {
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                write!(f, "(")?;
+                for (i, c) in self.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    fmt::Display::fmt(&c, f)?;
+                }
+                write!(f, ")")
+            }
+        }
+
+        impl<T: Scalar + FromStr> FromStr for $type<T> {
+            type Err = ParseVecError;
+            fn from_str(s: &str) -> Result<Self, ParseVecError> {
+                parse_components::<T, $n>(s).map(Self::from)
+            }
+        }
+
+        // Arrays and tuples are foreign, so these can only be done for the
+        // concrete floats, and the same goes for scalars on the left
+        impl_vec_conversions!(@float $type, $n; [$($i => $var),+]; f32);
+        impl_vec_conversions!(@float $type, $n; [$($i => $var),+]; f64);
+    };
+    (@float $type:ident, $n:expr; [$($i:tt => $var:ident),+]; $float:ident) => {
+        impl From<$type<$float>> for [$float; $n] {
+            fn from(v: $type<$float>) -> Self {
+                v.to_array()
+            }
+        }
+
+        impl From<$type<$float>> for ($(impl_vec_conversions!(@scalar $var $float)),+) {
+            fn from(v: $type<$float>) -> Self {
+                ($(v.$var),+)
+            }
+        }
+
+        impl Add<$type<$float>> for $float {
+            type Output = $type<$float>;
+            fn add(self, rhs: $type<$float>) -> $type<$float> {
+                rhs + self
+            }
+        }
+
+        impl Sub<$type<$float>> for $float {
+            type Output = $type<$float>;
+            fn sub(self, rhs: $type<$float>) -> $type<$float> {
+                $type { $($var: self - rhs.$var),+ }
+            }
+        }
+
+        impl Mul<$type<$float>> for $float {
+            type Output = $type<$float>;
+            fn mul(self, rhs: $type<$float>) -> $type<$float> {
+                rhs * self
+            }
+        }
+
+        impl Div<$type<$float>> for $float {
+            type Output = $type<$float>;
+            fn div(self, rhs: $type<$float>) -> $type<$float> {
+                $type { $($var: self / rhs.$var),+ }
+            }
+        }
+    };
+    (@scalar $var:ident $float:ident) => { $float };
+}
+
+// Runs $callback!(get $args [components]) for every sequence of 2 to 4
+// components, and $callback!(set $args [components]) for every one without
+// repeats. A component is a (name field) pair, so aliases can read x as r
+macro_rules! for_each_swizzle {
+    ($callback:ident $args:tt; $all:tt) => {
+        for_each_swizzle!(@get $callback $args; []; $all);
+        for_each_swizzle!(@set $callback $args; []; $all);
+    };
+
+    // any component can follow, up to four of them
+    (@get $callback:ident $args:tt; $chosen:tt; $all:tt) => {
+        for_each_swizzle!(@get_each $callback $args; $chosen; $all; $all);
+    };
+    (@get_each $callback:ident $args:tt; $chosen:tt; $all:tt; [$($c:tt)+]) => {
+        $(for_each_swizzle!(@get_push $callback $args; $chosen $c; $all);)+
+    };
+    (@get_push $callback:ident $args:tt; [$a:tt $b:tt $c:tt] $d:tt; $all:tt) => {
+        $callback!(get $args [$a $b $c $d]);
+    };
+    (@get_push $callback:ident $args:tt; [$($chosen:tt)*] $c:tt; $all:tt) => {
+        $callback!(get $args [$($chosen)* $c]);
+        for_each_swizzle!(@get $callback $args; [$($chosen)* $c]; $all);
+    };
+
+    // only the components not taken yet can follow, walking the rest
+    // as a zipper to pick each one in turn
+    (@set $callback:ident $args:tt; $chosen:tt; $rest:tt) => {
+        for_each_swizzle!(@set_pick $callback $args; $chosen; []; $rest);
+    };
+    (@set_pick $callback:ident $args:tt; $chosen:tt; [$($before:tt)*]; [$c:tt $($after:tt)*]) => {
+        for_each_swizzle!(@set_push $callback $args; $chosen $c; [$($before)* $($after)*]);
+        for_each_swizzle!(@set_pick $callback $args; $chosen; [$($before)* $c]; [$($after)*]);
+    };
+    (@set_pick $callback:ident $args:tt; $chosen:tt; $before:tt; []) => {};
+    (@set_push $callback:ident $args:tt; [$($chosen:tt)*] $c:tt; $rest:tt) => {
+        $callback!(set $args [$($chosen)* $c]);
+        for_each_swizzle!(@set $callback $args; [$($chosen)* $c]; $rest);
+    };
+}
+
 macro_rules! swizzle {
-    (($type:ty) $name:ident, $ax1:ident, $ax2:ident) => {
-        impl $type {
-            pub fn $name(&self) -> Vec2 {
-                vec2!(self.$ax1, self.$ax2)
+    (get $type:ident [($a:ident $fa:ident)]) => {};
+    (get $type:ident [($a:ident $fa:ident) ($b:ident $fb:ident)]) => {
+        ::paste::paste! {
+            impl<T: Scalar> $type<T> {
+                pub fn [<$a $b>](&self) -> Vec2<T> {
+                    Vec2::new(self.$fa, self.$fb)
+                }
+            }
+        }
+    };
+    (get $type:ident [($a:ident $fa:ident) ($b:ident $fb:ident) ($c:ident $fc:ident)]) => {
+        ::paste::paste! {
+            impl<T: Scalar> $type<T> {
+                pub fn [<$a $b $c>](&self) -> Vec3<T> {
+                    Vec3::new(self.$fa, self.$fb, self.$fc)
+                }
+            }
+        }
+    };
+    (get $type:ident [($a:ident $fa:ident) ($b:ident $fb:ident) ($c:ident $fc:ident) ($d:ident $fd:ident)]) => {
+        ::paste::paste! {
+            impl<T: Scalar> $type<T> {
+                pub fn [<$a $b $c $d>](&self) -> Vec4<T> {
+                    Vec4::new(self.$fa, self.$fb, self.$fc, self.$fd)
+                }
             }
         }
     };
-    (($type:ty) $name:ident, $ax1:ident, $ax2:ident, $ax3:ident) => {
-        impl $type {
-            pub fn $name(&self) -> Vec3 {
-                vec3!(self.$ax1, self.$ax2, self.$ax3)
+    (set $type:ident [($a:ident $fa:ident)]) => {};
+    (set $type:ident [($a:ident $fa:ident) ($b:ident $fb:ident)]) => {
+        ::paste::paste! {
+            impl<T: Scalar> $type<T> {
+                pub fn [<set_ $a $b>](&mut self, v: &Vec2<T>) {
+                    self.$fa = v.x;
+                    self.$fb = v.y;
+                }
             }
         }
     };
-    (($type:ty) $name:ident, $ax1:ident, $ax2:ident, $ax3:ident, $ax4:ident) => {
-        impl $type {
-            pub fn $name(&self) -> Vec4 {
-                vec4!(self.$ax1, self.$ax2, self.$ax3, self.$ax4)
+    (set $type:ident [($a:ident $fa:ident) ($b:ident $fb:ident) ($c:ident $fc:ident)]) => {
+        ::paste::paste! {
+            impl<T: Scalar> $type<T> {
+                pub fn [<set_ $a $b $c>](&mut self, v: &Vec3<T>) {
+                    self.$fa = v.x;
+                    self.$fb = v.y;
+                    self.$fc = v.z;
+                }
             }
         }
     };
+    (set $type:ident [($a:ident $fa:ident) ($b:ident $fb:ident) ($c:ident $fc:ident) ($d:ident $fd:ident)]) => {
+        ::paste::paste! {
+            impl<T: Scalar> $type<T> {
+                pub fn [<set_ $a $b $c $d>](&mut self, v: &Vec4<T>) {
+                    self.$fa = v.x;
+                    self.$fb = v.y;
+                    self.$fc = v.z;
+                    self.$fd = v.w;
+                }
+            }
+        }
+    };
+}
+
+// All swizzles of a vector type. The first list is the fields, the others
+// are aliases for them in the same order, like rgba for xyzw
+macro_rules! swizzles {
+    ($type:ident; $fields:tt $(, $aliases:tt)*) => {
+        swizzles!(@zip $type; $fields; $fields);
+        $(swizzles!(@zip $type; $aliases; $fields);)*
+    };
+    (@zip $type:ident; [$($name:ident)+]; [$($field:ident)+]) => {
+        for_each_swizzle!(swizzle $type; [$(($name $field))+]);
+    };
+}
+
+// assert_eq! for anything ApproxEq, either with the default relative
+// tolerance or within an absolute epsilon
+#[cfg(test)]
+macro_rules! assert_approx_eq {
+    ($left:expr, $right:expr) => {{
+        let (left, right) = (&$left, &$right);
+        assert!(
+            $crate::linalg::ApproxEq::approx_eq(left, right),
+            "assertion failed: `left ~= right`\n  left: `{:?}`\n right: `{:?}`", left, right
+        );
+    }};
+    ($left:expr, $right:expr, $epsilon:expr) => {{
+        let (left, right) = (&$left, &$right);
+        assert!(
+            $crate::linalg::ApproxEq::abs_diff_eq(left, right, $epsilon),
+            "assertion failed: `left ~= right` within {:?}\n  left: `{:?}`\n right: `{:?}`", $epsilon, left, right
+        );
+    }};
+}
+
+// Callback for for_each_swizzle that checks every generated swizzle reads
+// and writes the right fields, counting them as it goes
+#[cfg(test)]
+macro_rules! check_swizzle {
+    (get ($v:ident, $gets:ident, $sets:ident) [($a:ident $fa:ident)]) => {};
+    (get ($v:ident, $gets:ident, $sets:ident) [($a:ident $fa:ident) ($b:ident $fb:ident)]) => {
+        assert_eq!(::paste::paste!($v.[<$a $b>]()), Vec2::new($v.$fa, $v.$fb));
+        $gets += 1;
+    };
+    (get ($v:ident, $gets:ident, $sets:ident) [($a:ident $fa:ident) ($b:ident $fb:ident) ($c:ident $fc:ident)]) => {
+        assert_eq!(::paste::paste!($v.[<$a $b $c>]()), Vec3::new($v.$fa, $v.$fb, $v.$fc));
+        $gets += 1;
+    };
+    (get ($v:ident, $gets:ident, $sets:ident) [$(($a:ident $fa:ident))+]) => {
+        assert_eq!(::paste::paste!($v.[<$($a)+>]()), Vec4::new($($v.$fa),+));
+        $gets += 1;
+    };
+    (set ($v:ident, $gets:ident, $sets:ident) [($a:ident $fa:ident)]) => {};
+    (set ($v:ident, $gets:ident, $sets:ident) [$(($a:ident $fa:ident))+]) => {
+        // write the negated fields back, leaving the others alone
+        let mut u = $v;
+        let values = [$(-$v.$fa),+];
+        ::paste::paste!(u.[<set_ $($a)+>](&From::from(values)));
+        $(assert_eq!(u.$fa, -$v.$fa);)+
+        assert_eq!(u.iter().filter(|c| *c < 0.0).count(), values.len());
+        $sets += 1;
+    };
 }
 
+#[cfg(test)]
 macro_rules! generate_op_test {
     ($name:ident; $lhs:expr, $op:tt, $rhs:expr => $ans:expr) => {
         #[test]
         fn $name() {
-            assert_eq!($lhs $op $rhs, $ans);
+            assert_approx_eq!($lhs $op $rhs, $ans);
         }
     };
     ((assign) $name:ident; $lhs:expr, $op:tt, $rhs:expr => $ans:expr) => {
@@ -89,7 +532,94 @@ macro_rules! generate_op_test {
         fn $name() {
             let mut lhs = $lhs;
             lhs $op $rhs;
-            assert_eq!(lhs, $ans);
+            assert_approx_eq!(lhs, $ans);
         }
     };
-}
\ No newline at end of file
+}
+
+macro_rules! impl_mat_ops {
+    ($mat:ident, $vec:ident; [$($col:tt => $var:ident),+]) => {
+
+        impl Add for $mat {
+            type Output = Self;
+            fn add(self, rhs: Self) -> Self {
+                Self { cols: [$(self.cols[$col] + rhs.cols[$col]),+] }
+            }
+        }
+
+        impl Sub for $mat {
+            type Output = Self;
+            fn sub(self, rhs: Self) -> Self {
+                Self { cols: [$(self.cols[$col] - rhs.cols[$col]),+] }
+            }
+        }
+
+        impl Neg for $mat {
+            type Output = Self;
+            fn neg(self) -> Self {
+                Self { cols: [$(-self.cols[$col]),+] }
+            }
+        }
+
+        impl Mul<Real> for $mat {
+            type Output = Self;
+            fn mul(self, rhs: Real) -> Self {
+                Self { cols: [$(self.cols[$col] * rhs),+] }
+            }
+        }
+
+        // Column vector on the right, M * v
+        impl Mul<$vec> for $mat {
+            type Output = $vec;
+            fn mul(self, rhs: $vec) -> $vec {
+                $vec { $($var: 0.0),+ } $(+ self.cols[$col] * rhs.$var)+
+            }
+        }
+
+        // Row vector on the left, v * M, same as transpose(M) * v like in GLSL
+        impl Mul<$mat> for $vec {
+            type Output = $vec;
+            fn mul(self, rhs: $mat) -> $vec {
+                $vec { $($var: self.dot(&rhs.cols[$col])),+ }
+            }
+        }
+
+        impl Mul for $mat {
+            type Output = Self;
+            fn mul(self, rhs: Self) -> Self {
+                Self { cols: [$(self * rhs.cols[$col]),+] }
+            }
+        }
+
+        impl AddAssign for $mat {
+            fn add_assign(&mut self, rhs: Self) {
+                *self = *self + rhs;
+            }
+        }
+
+        impl SubAssign for $mat {
+            fn sub_assign(&mut self, rhs: Self) {
+                *self = *self - rhs;
+            }
+        }
+
+        impl MulAssign for $mat {
+            fn mul_assign(&mut self, rhs: Self) {
+                *self = *self * rhs;
+            }
+        }
+
+        impl MulAssign<Real> for $mat {
+            fn mul_assign(&mut self, rhs: Real) {
+                *self = *self * rhs;
+            }
+        }
+
+        impl MulAssign<$mat> for $vec {
+            fn mul_assign(&mut self, rhs: $mat) {
+                *self = *self * rhs;
+            }
+        }
+
+    };
+}
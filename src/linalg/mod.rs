@@ -1,9 +1,31 @@
 #[macro_use]
 pub mod macros;
+pub mod scalar;
+#[cfg(all(feature = "simd", target_arch = "x86_64"))]
+mod simd;
+pub mod parse;
 pub mod _vec2;
 pub mod _vec3;
 pub mod _vec4;
+pub mod _mat2;
+pub mod _mat3;
+pub mod _mat4;
+pub mod _quat;
+pub mod _dual;
+pub mod _packet;
+pub mod approx;
+#[cfg(test)]
+mod properties;
 
+pub use scalar::{Real, Scalar, Number, consts};
+pub use parse::ParseVecError;
 pub use _vec3::Vec3;
 pub use _vec2::Vec2;
-pub use _vec4::Vec4;
\ No newline at end of file
+pub use _vec4::Vec4;
+pub use _mat2::Mat2;
+pub use _mat3::Mat3;
+pub use _mat4::Mat4;
+pub use _quat::Quat;
+pub use _dual::{Dual, Dual3, Tangent};
+pub use _packet::{Packet, LANES};
+pub use approx::ApproxEq;
@@ -0,0 +1,40 @@
+use std::convert::TryInto;
+use std::fmt;
+use std::str::FromStr;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ParseVecError {
+    Length { expected: usize, found: usize },
+    Component(String)
+}
+
+impl fmt::Display for ParseVecError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParseVecError::Length {expected, found} =>
+                write!(f, "expected {} components, found {}", expected, found),
+            ParseVecError::Component(c) => write!(f, "invalid vector component {:?}", c)
+        }
+    }
+}
+
+impl std::error::Error for ParseVecError {}
+
+// Splits "1, 2, 3" into its numbers. Surrounding parentheses or brackets
+// are allowed, so whatever Display prints parses back
+pub(crate) fn parse_components<T: FromStr, const N: usize>(s: &str) -> Result<[T; N], ParseVecError> {
+    let s = s.trim();
+    let s = s.strip_prefix('(').and_then(|s| s.strip_suffix(')'))
+        .or_else(|| s.strip_prefix('[').and_then(|s| s.strip_suffix(']')))
+        .unwrap_or(s);
+    let parts: Vec<&str> = s.split(',').map(str::trim).collect();
+    if parts.len() != N {
+        return Err(ParseVecError::Length {expected: N, found: parts.len()});
+    }
+    let mut values = Vec::with_capacity(N);
+    for part in parts {
+        values.push(part.parse().map_err(|_| ParseVecError::Component(part.to_string()))?);
+    }
+    // the length was checked above
+    values.try_into().map_err(|_| unreachable!())
+}
@@ -0,0 +1,70 @@
+// Identities that should hold for any input, checked on random vectors
+use proptest::prelude::*;
+use crate::linalg::{ApproxEq, Real, Vec2, Vec3, Vec4, Mat3, Quat};
+
+const RANGE: Real = 1e3;
+
+fn component() -> impl Strategy<Value = Real> {
+    -RANGE..RANGE
+}
+
+fn vec2() -> impl Strategy<Value = Vec2> {
+    (component(), component()).prop_map(Vec2::from)
+}
+
+fn vec3() -> impl Strategy<Value = Vec3> {
+    (component(), component(), component()).prop_map(Vec3::from)
+}
+
+fn vec4() -> impl Strategy<Value = Vec4> {
+    (component(), component(), component(), component()).prop_map(Vec4::from)
+}
+
+// Rounding errors grow with the size of the inputs
+fn tolerance(scale: Real) -> Real {
+    64.0 * Real::EPSILON * scale
+}
+
+proptest! {
+    #[test]
+    fn normalized_has_unit_length(v2 in vec2(), v3 in vec3(), v4 in vec4()) {
+        prop_assume!(v2.len() > 1e-6 && v3.len() > 1e-6 && v4.len() > 1e-6);
+        prop_assert!(v2.normalized().len().approx_eq(&1.0));
+        prop_assert!(v3.normalized().len().approx_eq(&1.0));
+        prop_assert!(v4.normalized().len().approx_eq(&1.0));
+    }
+
+    #[test]
+    fn cross_is_orthogonal(a in vec3(), b in vec3()) {
+        let c = a.cross(&b);
+        let scale = a.len() * a.len() * b.len();
+        prop_assert!(c.dot(&a).abs_diff_eq(&0.0, tolerance(scale)));
+        prop_assert!(c.dot(&b).abs_diff_eq(&0.0, tolerance(scale)));
+        prop_assert!(c.approx_eq(&-b.cross(&a)));
+    }
+
+    #[test]
+    fn triangle_inequality(a in vec3(), b in vec3()) {
+        prop_assert!((a + b).len() <= a.len() + b.len() + tolerance(a.len() + b.len()));
+    }
+
+    #[test]
+    fn cauchy_schwarz(a in vec4(), b in vec4()) {
+        prop_assert!(a.dot(&b).abs() <= a.len() * b.len() + tolerance(a.len() * b.len()));
+    }
+
+    #[test]
+    fn rotations_preserve_length(v in vec3(), axis in vec3(), angle in -10.0..10.0 as Real) {
+        prop_assume!(axis.len() > 1e-6);
+        let q = Quat::from_axis_angle(&axis, angle);
+        let m = Mat3::rotation(&axis, angle);
+        prop_assert!(q.rotate(&v).len().abs_diff_eq(&v.len(), tolerance(v.len())));
+        prop_assert!(q.rotate(&v).abs_diff_eq(&(m * v), tolerance(v.len())));
+        prop_assert!((m * m.transpose()).abs_diff_eq(&Mat3::identity(), tolerance(1.0)));
+    }
+
+    #[test]
+    fn display_parses_back(v in vec3()) {
+        prop_assert_eq!(v.to_string().parse::<Vec3>(), Ok(v));
+    }
+}
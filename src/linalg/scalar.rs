@@ -0,0 +1,191 @@
+use std::fmt::Debug;
+use std::ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Neg, Sub, SubAssign, Rem};
+#[cfg(all(feature = "simd", target_arch = "x86_64"))]
+use crate::linalg::simd;
+
+// The number type the renderer works in. f64 by default, build with
+// --features f32 to trade precision for speed
+#[cfg(not(feature = "f32"))]
+pub type Real = f64;
+#[cfg(feature = "f32")]
+pub type Real = f32;
+
+#[cfg(not(feature = "f32"))]
+pub use std::f64::consts;
+#[cfg(feature = "f32")]
+pub use std::f32::consts;
+
+// What the vector types need from their components. Not ordered, since a
+// packet's lanes can disagree, so generic code branches through select_lt
+pub trait Scalar:
+    Copy + Debug + PartialEq
+    + Add<Output = Self> + Sub<Output = Self> + Mul<Output = Self> + Div<Output = Self>
+    + Rem<Output = Self> + Neg<Output = Self>
+    + AddAssign + SubAssign + MulAssign + DivAssign
+{
+    fn from_f64(x: f64) -> Self;
+    fn sqrt(self) -> Self;
+    fn abs(self) -> Self;
+    fn min(self, other: Self) -> Self;
+    fn max(self, other: Self) -> Self;
+    fn floor(self) -> Self;
+    fn signum(self) -> Self;
+    fn sin(self) -> Self;
+    fn cos(self) -> Self;
+    fn acos(self) -> Self;
+    fn atan2(self, x: Self) -> Self;
+    fn powf(self, n: Self) -> Self;
+    fn ln(self) -> Self;
+
+    fn from_real(x: Real) -> Self {
+        Self::from_f64(x.to_f64())
+    }
+
+    // if_less where self < other and otherwise elsewhere, lane by lane
+    fn select_lt(self, other: Self, if_less: Self, otherwise: Self) -> Self;
+
+    // Four lanes at a time, which Vec3 and Vec4 build their arithmetic on
+    // when LANE_KERNELS is set. Plain loops here, SIMD for f32 and f64 with
+    // --features simd, the only types that set it
+    const LANE_KERNELS: bool = false;
+
+    #[inline]
+    fn add4(a: [Self; 4], b: [Self; 4]) -> [Self; 4] {
+        lanewise(a, b, |a, b| a + b)
+    }
+
+    #[inline]
+    fn sub4(a: [Self; 4], b: [Self; 4]) -> [Self; 4] {
+        lanewise(a, b, |a, b| a - b)
+    }
+
+    #[inline]
+    fn mul4(a: [Self; 4], b: [Self; 4]) -> [Self; 4] {
+        lanewise(a, b, |a, b| a * b)
+    }
+
+    #[inline]
+    fn div4(a: [Self; 4], b: [Self; 4]) -> [Self; 4] {
+        lanewise(a, b, |a, b| a / b)
+    }
+
+    #[inline]
+    fn dot4(a: [Self; 4], b: [Self; 4]) -> Self {
+        a[0] * b[0] + a[1] * b[1] + a[2] * b[2] + a[3] * b[3]
+    }
+}
+
+// Scalars with a single value, which can be compared and read back out.
+// Loops that stop early, like the fractal iterations, need one of these
+pub trait Number: Scalar + PartialOrd {
+    fn to_f64(self) -> f64;
+}
+
+#[inline]
+fn lanewise<T: Copy, F: Fn(T, T) -> T>(a: [T; 4], b: [T; 4], f: F) -> [T; 4] {
+    [f(a[0], b[0]), f(a[1], b[1]), f(a[2], b[2]), f(a[3], b[3])]
+}
+
+macro_rules! impl_scalar {
+    ($($float:ident => $lanes:ident),+) => {
+        $(
+            impl Scalar for $float {
+                const LANE_KERNELS: bool = cfg!(all(feature = "simd", target_arch = "x86_64"));
+
+                fn from_f64(x: f64) -> Self {
+                    x as $float
+                }
+
+                fn select_lt(self, other: Self, if_less: Self, otherwise: Self) -> Self {
+                    if self < other { if_less } else { otherwise }
+                }
+
+                fn sqrt(self) -> Self {
+                    $float::sqrt(self)
+                }
+
+                fn abs(self) -> Self {
+                    $float::abs(self)
+                }
+
+                fn min(self, other: Self) -> Self {
+                    $float::min(self, other)
+                }
+
+                fn max(self, other: Self) -> Self {
+                    $float::max(self, other)
+                }
+
+                fn floor(self) -> Self {
+                    $float::floor(self)
+                }
+
+                fn signum(self) -> Self {
+                    $float::signum(self)
+                }
+
+                fn sin(self) -> Self {
+                    $float::sin(self)
+                }
+
+                fn cos(self) -> Self {
+                    $float::cos(self)
+                }
+
+                fn acos(self) -> Self {
+                    $float::acos(self)
+                }
+
+                fn atan2(self, x: Self) -> Self {
+                    $float::atan2(self, x)
+                }
+
+                fn powf(self, n: Self) -> Self {
+                    $float::powf(self, n)
+                }
+
+                fn ln(self) -> Self {
+                    $float::ln(self)
+                }
+
+                #[cfg(all(feature = "simd", target_arch = "x86_64"))]
+                #[inline]
+                fn add4(a: [Self; 4], b: [Self; 4]) -> [Self; 4] {
+                    simd::$lanes::add(a, b)
+                }
+
+                #[cfg(all(feature = "simd", target_arch = "x86_64"))]
+                #[inline]
+                fn sub4(a: [Self; 4], b: [Self; 4]) -> [Self; 4] {
+                    simd::$lanes::sub(a, b)
+                }
+
+                #[cfg(all(feature = "simd", target_arch = "x86_64"))]
+                #[inline]
+                fn mul4(a: [Self; 4], b: [Self; 4]) -> [Self; 4] {
+                    simd::$lanes::mul(a, b)
+                }
+
+                #[cfg(all(feature = "simd", target_arch = "x86_64"))]
+                #[inline]
+                fn div4(a: [Self; 4], b: [Self; 4]) -> [Self; 4] {
+                    simd::$lanes::div(a, b)
+                }
+
+                #[cfg(all(feature = "simd", target_arch = "x86_64"))]
+                #[inline]
+                fn dot4(a: [Self; 4], b: [Self; 4]) -> Self {
+                    simd::$lanes::dot(a, b)
+                }
+            }
+
+            impl Number for $float {
+                fn to_f64(self) -> f64 {
+                    self as f64
+                }
+            }
+        )+
+    };
+}
+
+impl_scalar!(f32 => f32x4, f64 => f64x4);
@@ -0,0 +1,86 @@
+// Four lane kernels for f32 and f64 on x86_64, on SSE2 which is always
+// there. f64 stays on two 128 bit halves even where AVX is enabled: for a
+// single vector the 256 bit registers cost more in loads, lane crossing and
+// clock speed than they save, and rendered slower than SSE2 in the benchmarks
+
+use std::arch::x86_64::*;
+
+macro_rules! lanewise {
+    ($load:ident, $store:ident, $zero:expr; $($name:ident => $intrinsic:ident),+) => {
+        $(
+            #[inline]
+            pub fn $name(a: [Lane; 4], b: [Lane; 4]) -> [Lane; 4] {
+                let mut out = [$zero; 4];
+                // The arrays are four lanes long, and the unaligned loads and
+                // stores never read or write past them
+                unsafe {
+                    for i in (0..4).step_by(WIDTH) {
+                        let r = $intrinsic($load(a.as_ptr().add(i)), $load(b.as_ptr().add(i)));
+                        $store(out.as_mut_ptr().add(i), r);
+                    }
+                }
+                out
+            }
+        )+
+    };
+}
+
+pub mod f64x4 {
+    use super::*;
+
+    type Lane = f64;
+
+    const WIDTH: usize = 2;
+    lanewise!(_mm_loadu_pd, _mm_storeu_pd, 0.0; add => _mm_add_pd, sub => _mm_sub_pd, mul => _mm_mul_pd, div => _mm_div_pd);
+
+    #[inline]
+    pub fn dot(a: [f64; 4], b: [f64; 4]) -> f64 {
+        unsafe {
+            let lo = _mm_mul_pd(_mm_loadu_pd(a.as_ptr()), _mm_loadu_pd(b.as_ptr()));
+            let hi = _mm_mul_pd(_mm_loadu_pd(a.as_ptr().add(2)), _mm_loadu_pd(b.as_ptr().add(2)));
+            let sum = _mm_add_pd(lo, hi);
+            _mm_cvtsd_f64(_mm_add_sd(sum, _mm_unpackhi_pd(sum, sum)))
+        }
+    }
+}
+
+pub mod f32x4 {
+    use super::*;
+
+    type Lane = f32;
+
+    const WIDTH: usize = 4;
+    lanewise!(_mm_loadu_ps, _mm_storeu_ps, 0.0; add => _mm_add_ps, sub => _mm_sub_ps, mul => _mm_mul_ps, div => _mm_div_ps);
+
+    #[inline]
+    pub fn dot(a: [f32; 4], b: [f32; 4]) -> f32 {
+        unsafe {
+            let prod = _mm_mul_ps(_mm_loadu_ps(a.as_ptr()), _mm_loadu_ps(b.as_ptr()));
+            let pairs = _mm_add_ps(prod, _mm_movehl_ps(prod, prod));
+            _mm_cvtss_f32(_mm_add_ss(pairs, _mm_shuffle_ps(pairs, pairs, 1)))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matches_scalar() {
+        let a = [1.5, -2.0, 3.25, 0.5];
+        let b = [4.0, 0.5, -1.0, 2.0];
+        assert_eq!(f64x4::add(a, b), [5.5, -1.5, 2.25, 2.5]);
+        assert_eq!(f64x4::sub(a, b), [-2.5, -2.5, 4.25, -1.5]);
+        assert_eq!(f64x4::mul(a, b), [6.0, -1.0, -3.25, 1.0]);
+        assert_eq!(f64x4::div(a, b), [0.375, -4.0, -3.25, 0.25]);
+        assert_eq!(f64x4::dot(a, b), 2.75);
+
+        let a = [1.5f32, -2.0, 3.25, 0.5];
+        let b = [4.0f32, 0.5, -1.0, 2.0];
+        assert_eq!(f32x4::add(a, b), [5.5, -1.5, 2.25, 2.5]);
+        assert_eq!(f32x4::mul(a, b), [6.0, -1.0, -3.25, 1.0]);
+        assert_eq!(f32x4::div(a, b), [0.375, -4.0, -3.25, 0.25]);
+        assert_eq!(f32x4::dot(a, b), 2.75);
+    }
+}
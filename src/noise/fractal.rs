@@ -0,0 +1,95 @@
+use crate::linalg::{Real, Vec2, Vec3, Vec4};
+use crate::noise::Noise;
+
+// Fractal Brownian motion: octaves of the same noise at rising frequency
+// and falling amplitude, normalized back into the range of the base noise
+#[derive(Debug, Clone, Copy)]
+pub struct Fbm<N: Noise> {
+    noise: N,
+    octaves: u32,
+    lacunarity: Real,
+    gain: Real
+}
+
+impl<N: Noise> Fbm<N> {
+    pub fn new(noise: N, octaves: u32, lacunarity: Real, gain: Real) -> Self {
+        assert!(octaves >= 1, "fbm needs at least one octave");
+        Self {noise, octaves, lacunarity, gain}
+    }
+
+    fn sum<F: Fn(Real, Real) -> Real>(&self, octave: F) -> Real {
+        let mut total = 0.0;
+        let mut norm = 0.0;
+        let mut frequency = 1.0;
+        let mut amplitude = 1.0;
+        for i in 0..self.octaves {
+            // shift each octave so their lattices don't line up at the origin
+            total += amplitude * octave(frequency, i as Real * 17.31);
+            norm += amplitude;
+            frequency *= self.lacunarity;
+            amplitude *= self.gain;
+        }
+        total / norm
+    }
+}
+
+impl<N: Noise> Noise for Fbm<N> {
+    fn noise2(&self, p: &Vec2) -> Real {
+        self.sum(|frequency, shift| self.noise.noise2(&(*p * frequency + shift)))
+    }
+
+    fn noise3(&self, p: &Vec3) -> Real {
+        self.sum(|frequency, shift| self.noise.noise3(&(*p * frequency + shift)))
+    }
+
+    fn noise4(&self, p: &Vec4) -> Real {
+        self.sum(|frequency, shift| self.noise.noise4(&(*p * frequency + shift)))
+    }
+}
+
+// Domain warping: offsets the sample position by another noise before
+// looking up the first one, giving swirly, marbled patterns
+#[derive(Debug, Clone, Copy)]
+pub struct Warp<N: Noise, W: Noise> {
+    noise: N,
+    warp: W,
+    strength: Real
+}
+
+impl<N: Noise, W: Noise> Warp<N, W> {
+    pub fn new(noise: N, warp: W, strength: Real) -> Self {
+        Self {noise, warp, strength}
+    }
+}
+
+// Arbitrary offsets decorrelating the components of the warp
+const SHIFTS: [Real; 4] = [0.0, 5.2, 1.3, 8.3];
+
+impl<N: Noise, W: Noise> Noise for Warp<N, W> {
+    fn noise2(&self, p: &Vec2) -> Real {
+        let q = vec2!(
+            self.warp.noise2(&(*p + SHIFTS[0])),
+            self.warp.noise2(&(*p + SHIFTS[1]))
+        );
+        self.noise.noise2(&(*p + q * self.strength))
+    }
+
+    fn noise3(&self, p: &Vec3) -> Real {
+        let q = vec3!(
+            self.warp.noise3(&(*p + SHIFTS[0])),
+            self.warp.noise3(&(*p + SHIFTS[1])),
+            self.warp.noise3(&(*p + SHIFTS[2]))
+        );
+        self.noise.noise3(&(*p + q * self.strength))
+    }
+
+    fn noise4(&self, p: &Vec4) -> Real {
+        let q = vec4!(
+            self.warp.noise4(&(*p + SHIFTS[0])),
+            self.warp.noise4(&(*p + SHIFTS[1])),
+            self.warp.noise4(&(*p + SHIFTS[2])),
+            self.warp.noise4(&(*p + SHIFTS[3]))
+        );
+        self.noise.noise4(&(*p + q * self.strength))
+    }
+}
@@ -0,0 +1,164 @@
+pub mod perlin;
+pub mod simplex;
+pub mod worley;
+pub mod fractal;
+
+pub use perlin::Perlin;
+pub use simplex::Simplex;
+pub use worley::Worley;
+pub use fractal::{Fbm, Warp};
+
+use crate::linalg::{Real, Vec2, Vec3, Vec4};
+use crate::objects::ScalarField;
+
+// Every noise is a pure function of its seed and the sample position,
+// so the same seed always gives the same picture.
+// Gradient noises land in [-1, 1], cellular noise in [0, ~1]
+pub trait Noise {
+    fn noise2(&self, p: &Vec2) -> Real;
+    fn noise3(&self, p: &Vec3) -> Real;
+    fn noise4(&self, p: &Vec4) -> Real;
+}
+
+// Lets any noise be used directly as a displacement field
+macro_rules! impl_scalar_field {
+    ($type:ty $(, $param:ident)*) => {
+        impl<$($param: Noise),*> ScalarField for $type {
+            fn sample(&self, z: &Vec3) -> Real {
+                self.noise3(z)
+            }
+        }
+    };
+}
+
+impl_scalar_field!(Perlin);
+impl_scalar_field!(Simplex);
+impl_scalar_field!(Worley);
+impl_scalar_field!(Fbm<N>, N);
+impl_scalar_field!(Warp<N, W>, N, W);
+
+// lowbias32 by Chris Wellons
+fn mix(mut h: u32) -> u32 {
+    h ^= h >> 16;
+    h = h.wrapping_mul(0x7feb_352d);
+    h ^= h >> 15;
+    h = h.wrapping_mul(0x846c_a68b);
+    h ^= h >> 16;
+    h
+}
+
+pub(crate) fn hash<const N: usize>(seed: u32, cell: &[i64; N], salt: u32) -> u32 {
+    let mut h = mix(seed ^ 0x9e37_79b9);
+    for &c in cell.iter() {
+        h = mix(h ^ (c as u32)) ^ mix((c >> 32) as u32);
+    }
+    mix(h ^ salt)
+}
+
+// Uniform in [0, 1)
+pub(crate) fn hash_unit<const N: usize>(seed: u32, cell: &[i64; N], salt: u32) -> Real {
+    (hash(seed, cell, salt) >> 8) as Real / (1u32 << 24) as Real
+}
+
+// Pseudo-random unit vector attached to a lattice point
+pub(crate) fn gradient<const N: usize>(seed: u32, cell: &[i64; N]) -> [Real; N] {
+    let mut g = [0.0; N];
+    for (i, gi) in g.iter_mut().enumerate() {
+        *gi = hash_unit(seed, cell, i as u32) * 2.0 - 1.0;
+    }
+    let len = g.iter().map(|x| x * x).sum::<Real>().sqrt();
+    if len < 1e-6 {
+        g[0] = 1.0;
+        return g;
+    }
+    g.iter_mut().for_each(|x| *x /= len);
+    g
+}
+
+pub(crate) fn dot<const N: usize>(a: &[Real; N], b: &[Real; N]) -> Real {
+    a.iter().zip(b.iter()).map(|(a, b)| a * b).sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn points() -> Vec<Vec4> {
+        // a deterministic scatter without pulling in a rng
+        (0..500).map(|i| {
+            let i = i as Real;
+            vec4!((i * 0.618).sin() * 20.0, (i * 1.303).cos() * 20.0, i * 0.071, (i * 2.1).sin() * 3.0)
+        }).collect()
+    }
+
+    #[test]
+    fn deterministic_and_seeded() {
+        let p = vec3!(1.3, -2.7, 0.4);
+        assert_eq!(Perlin::new(1).noise3(&p), Perlin::new(1).noise3(&p));
+        assert_ne!(Perlin::new(1).noise3(&p), Perlin::new(2).noise3(&p));
+        assert_eq!(Simplex::new(5).noise3(&p), Simplex::new(5).noise3(&p));
+        assert_ne!(Simplex::new(5).noise3(&p), Simplex::new(6).noise3(&p));
+        assert_eq!(Worley::new(9).noise3(&p), Worley::new(9).noise3(&p));
+        assert_ne!(Worley::new(9).noise3(&p), Worley::new(10).noise3(&p));
+    }
+
+    #[test]
+    fn perlin_vanishes_on_the_lattice() {
+        let perlin = Perlin::new(3);
+        assert_eq!(perlin.noise2(&vec2!(4, -7)), 0.0);
+        assert_eq!(perlin.noise3(&vec3!(1, 2, -3)), 0.0);
+        assert_eq!(perlin.noise4(&vec4!(0, 5, 1, -1)), 0.0);
+    }
+
+    #[test]
+    fn gradient_noise_in_range() {
+        let noises: [&dyn Noise; 2] = [&Perlin::new(11), &Simplex::new(11)];
+        for noise in noises.iter() {
+            for p in points() {
+                assert!(noise.noise2(&p.xy()).abs() <= 1.0);
+                assert!(noise.noise3(&p.xyz()).abs() <= 1.0);
+                assert!(noise.noise4(&p).abs() <= 1.0);
+            }
+        }
+    }
+
+    #[test]
+    fn worley_distances_are_ordered() {
+        let worley = Worley::new(4);
+        for p in points() {
+            let (f1, f2) = worley.distances3(&p.xyz());
+            assert!(0.0 <= f1 && f1 <= f2);
+            // some feature point is always within the cell's diagonal
+            assert!(f1 <= Real::sqrt(3.0));
+        }
+    }
+
+    #[test]
+    fn worley_looks_past_the_neighbouring_cells() {
+        // the second nearest point here is two cells away
+        let p = [7.463, 2.007];
+        let wide = worley::nearest_two(1, &p, &[7, 2], 3);
+        assert_ne!(worley::nearest_two(1, &p, &[7, 2], 1), wide);
+        assert_eq!(worley::worley(1, p), wide);
+    }
+
+    #[test]
+    fn single_octave_fbm_is_the_base_noise() {
+        let p = vec3!(0.2, 0.9, -4.1);
+        let fbm = Fbm::new(Perlin::new(8), 1, 2.0, 0.5);
+        assert_eq!(fbm.noise3(&p), Perlin::new(8).noise3(&p));
+    }
+
+    #[test]
+    #[should_panic(expected = "at least one octave")]
+    fn fbm_needs_an_octave() {
+        Fbm::new(Perlin::new(8), 0, 2.0, 0.5);
+    }
+
+    #[test]
+    fn unwarped_is_the_base_noise() {
+        let p = vec2!(3.3, 1.7);
+        let warp = Warp::new(Simplex::new(2), Perlin::new(3), 0.0);
+        assert_eq!(warp.noise2(&p), Simplex::new(2).noise2(&p));
+    }
+}
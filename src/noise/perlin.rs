@@ -0,0 +1,61 @@
+use crate::linalg::{Real, Vec2, Vec3, Vec4};
+use crate::noise::{Noise, gradient, dot};
+
+// Classic gradient noise on the integer lattice
+#[derive(Debug, Clone, Copy)]
+pub struct Perlin {
+    seed: u32
+}
+
+impl Perlin {
+    pub fn new(seed: u32) -> Self {
+        Self {seed}
+    }
+}
+
+fn fade(t: Real) -> Real {
+    t * t * t * (t * (t * 6.0 - 15.0) + 10.0)
+}
+
+pub(crate) fn perlin<const N: usize>(seed: u32, p: [Real; N]) -> Real {
+    let mut cell = [0i64; N];
+    let mut frac = [0.0; N];
+    for i in 0..N {
+        let floor = p[i].floor();
+        cell[i] = floor as i64;
+        frac[i] = p[i] - floor;
+    }
+    // visit all 2^N corners, weighting each by the multilinear fade
+    let mut total = 0.0;
+    for corner in 0..(1usize << N) {
+        let mut lattice = cell;
+        let mut offset = frac;
+        let mut weight = 1.0;
+        for i in 0..N {
+            if corner & (1 << i) != 0 {
+                lattice[i] += 1;
+                offset[i] -= 1.0;
+                weight *= fade(frac[i]);
+            } else {
+                weight *= 1.0 - fade(frac[i]);
+            }
+        }
+        total += weight * dot(&gradient(seed, &lattice), &offset);
+    }
+    // with unit gradients the extremes are +-sqrt(N) / 2
+    total * 2.0 / (N as Real).sqrt()
+}
+
+impl Noise for Perlin {
+    fn noise2(&self, p: &Vec2) -> Real {
+        perlin(self.seed, [p.x, p.y])
+    }
+
+    fn noise3(&self, p: &Vec3) -> Real {
+        perlin(self.seed, [p.x, p.y, p.z])
+    }
+
+    fn noise4(&self, p: &Vec4) -> Real {
+        perlin(self.seed, [p.x, p.y, p.z, p.w])
+    }
+}
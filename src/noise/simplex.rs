@@ -0,0 +1,129 @@
+use crate::linalg::{Real, Vec2, Vec3, Vec4};
+use crate::noise::{Noise, gradient, dot};
+
+// Gradient noise on the simplex lattice. Cheaper than Perlin in higher
+// dimensions since it only visits N + 1 corners instead of 2^N
+#[derive(Debug, Clone, Copy)]
+pub struct Simplex {
+    seed: u32
+}
+
+impl Simplex {
+    pub fn new(seed: u32) -> Self {
+        Self {seed}
+    }
+}
+
+// One over the largest value the sum can reach, so the output stays in
+// [-1, 1]. With unit gradients that's when each one points along its offset,
+// making the sum of (0.5 - r^2)^4 * r over the corners. In 2D it peaks
+// halfway along an edge at 2 / (81 * sqrt(6)); in 3D and 4D the peaks
+// were found numerically and are rounded down here
+fn scale(n: usize) -> Real {
+    match n {
+        2 => 81.0 * Real::sqrt(6.0) / 2.0,
+        3 => 107.6534,
+        _ => 108.5678
+    }
+}
+
+// Calls f with each of the N + 1 corners of the simplex containing p, and
+// p's offset from it
+fn for_each_corner<const N: usize, F: FnMut(&[i64; N], &[Real; N])>(p: [Real; N], mut f: F) {
+    let n = N as Real;
+    let skew = ((n + 1.0).sqrt() - 1.0) / n;
+    let unskew = (1.0 - 1.0 / (n + 1.0).sqrt()) / n;
+
+    let s = p.iter().sum::<Real>() * skew;
+    let mut cell = [0i64; N];
+    let mut skewed_floor = [0.0; N];
+    for i in 0..N {
+        skewed_floor[i] = (p[i] + s).floor();
+        cell[i] = skewed_floor[i] as i64;
+    }
+    let t = skewed_floor.iter().sum::<Real>() * unskew;
+    let mut d = [0.0; N];
+    for i in 0..N {
+        d[i] = p[i] - (skewed_floor[i] - t);
+    }
+
+    // walk from the origin corner towards the far one, stepping along
+    // the axes in order of decreasing distance
+    let mut order = [0usize; N];
+    for (i, o) in order.iter_mut().enumerate() {
+        *o = i;
+    }
+    order.sort_by(|&a, &b| d[b].total_cmp(&d[a]));
+
+    let mut lattice = cell;
+    for k in 0..=N {
+        if k > 0 {
+            lattice[order[k - 1]] += 1;
+        }
+        let mut offset = [0.0; N];
+        for i in 0..N {
+            offset[i] = d[i] - (lattice[i] - cell[i]) as Real + k as Real * unskew;
+        }
+        f(&lattice, &offset);
+    }
+}
+
+// How much a corner at this offset contributes, before the gradient
+fn falloff<const N: usize>(offset: &[Real; N]) -> Real {
+    let falloff = (0.5 - dot(offset, offset)).max(0.0);
+    falloff * falloff * falloff * falloff
+}
+
+pub(crate) fn simplex<const N: usize>(seed: u32, p: [Real; N]) -> Real {
+    let mut total = 0.0;
+    for_each_corner(p, |lattice, offset| {
+        total += falloff(offset) * dot(&gradient(seed, lattice), offset);
+    });
+    total * scale(N)
+}
+
+impl Noise for Simplex {
+    fn noise2(&self, p: &Vec2) -> Real {
+        simplex(self.seed, [p.x, p.y])
+    }
+
+    fn noise3(&self, p: &Vec3) -> Real {
+        simplex(self.seed, [p.x, p.y, p.z])
+    }
+
+    fn noise4(&self, p: &Vec4) -> Real {
+        simplex(self.seed, [p.x, p.y, p.z, p.w])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::linalg::approx::EPSILON;
+
+    // The sum with every gradient pointing along its offset
+    fn worst_case<const N: usize>(p: [Real; N]) -> Real {
+        let mut total = 0.0;
+        for_each_corner(p, |_, offset| total += falloff(offset) * dot(offset, offset).sqrt());
+        total * scale(N)
+    }
+
+    #[test]
+    fn scale_bounds_the_worst_case() {
+        let mut peak: [Real; 3] = [0.0; 3];
+        for i in 0..20000 {
+            let t = i as Real;
+            let p = [(t * 0.618).fract() * 3.0, (t * 0.754).fract() * 3.0, (t * 0.569).fract() * 3.0, (t * 0.883).fract() * 3.0];
+            peak[0] = peak[0].max(worst_case([p[0], p[1]]));
+            peak[1] = peak[1].max(worst_case([p[0], p[1], p[2]]));
+            peak[2] = peak[2].max(worst_case(p));
+        }
+        // close to one, without going over
+        for &v in peak.iter() {
+            assert!(v <= 1.0 && v > 0.95, "{}", v);
+        }
+        // the 2D peak, halfway along an edge
+        let unskew = (1.0 - 1.0 / Real::sqrt(3.0)) / 2.0;
+        assert!((worst_case([0.5 - unskew / 2.0, -unskew / 2.0]) - 1.0).abs() < EPSILON);
+    }
+}
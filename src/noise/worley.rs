@@ -0,0 +1,88 @@
+use crate::linalg::{Real, Vec2, Vec3, Vec4};
+use crate::noise::{Noise, hash_unit};
+
+// Cellular noise: one random feature point per unit cell, the value being
+// the distance to the nearest of them
+#[derive(Debug, Clone, Copy)]
+pub struct Worley {
+    seed: u32
+}
+
+impl Worley {
+    pub fn new(seed: u32) -> Self {
+        Self {seed}
+    }
+
+    // Distances to the nearest and second nearest feature points
+    pub fn distances3(&self, p: &Vec3) -> (Real, Real) {
+        worley(self.seed, [p.x, p.y, p.z])
+    }
+}
+
+pub(crate) fn worley<const N: usize>(seed: u32, p: [Real; N]) -> (Real, Real) {
+    let mut cell = [0i64; N];
+    for i in 0..N {
+        cell[i] = p[i].floor() as i64;
+    }
+    // Usually the 3^N block around the cell holds both neighbours, but
+    // not always. Grow the block until nothing outside it can be closer
+    // than the second nearest point found
+    let mut radius = 1;
+    loop {
+        let (f1, f2) = nearest_two(seed, &p, &cell, radius);
+        let mut reach = 1.0 / 0.0;
+        for i in 0..N {
+            let below = p[i] - (cell[i] - radius) as Real;
+            let above = (cell[i] + radius + 1) as Real - p[i];
+            reach = below.min(above).min(reach);
+        }
+        if f2 <= reach {
+            return (f1, f2);
+        }
+        radius += 1;
+    }
+}
+
+// The two nearest feature points in the block of cells within radius of cell
+pub(crate) fn nearest_two<const N: usize>(seed: u32, p: &[Real; N], cell: &[i64; N], radius: i64) -> (Real, Real) {
+    let side = 2 * radius as usize + 1;
+    let mut f1 = 1.0 / 0.0;
+    let mut f2 = 1.0 / 0.0;
+    let mut neighbour = 0;
+    while neighbour < side.pow(N as u32) {
+        let mut lattice = *cell;
+        let mut rest = neighbour;
+        for l in lattice.iter_mut() {
+            *l += (rest % side) as i64 - radius;
+            rest /= side;
+        }
+        let mut dist = 0.0;
+        for i in 0..N {
+            let feature = lattice[i] as Real + hash_unit(seed, &lattice, i as u32);
+            dist += (feature - p[i]) * (feature - p[i]);
+        }
+        let dist = dist.sqrt();
+        if dist < f1 {
+            f2 = f1;
+            f1 = dist;
+        } else if dist < f2 {
+            f2 = dist;
+        }
+        neighbour += 1;
+    }
+    (f1, f2)
+}
+
+impl Noise for Worley {
+    fn noise2(&self, p: &Vec2) -> Real {
+        worley(self.seed, [p.x, p.y]).0
+    }
+
+    fn noise3(&self, p: &Vec3) -> Real {
+        worley(self.seed, [p.x, p.y, p.z]).0
+    }
+
+    fn noise4(&self, p: &Vec4) -> Real {
+        worley(self.seed, [p.x, p.y, p.z, p.w]).0
+    }
+}
@@ -1,14 +1,36 @@
-use crate::linalg::Vec3;
+use crate::linalg::{Real, Scalar, Dual3, Packet, Vec3};
 
-pub struct Scene(Vec<Box<Object>>);
+pub struct Scene(Vec<Box<dyn Object>>);
+
+impl Default for Scene {
+    fn default() -> Self {
+        Self::new()
+    }
+}
 
 impl Scene {
     pub fn new() -> Self {
         Self(vec![])
     }
 
-    pub fn distance_estimator(&self, z: &Vec3) -> f64 {
-        self.0.iter().map(|obj| obj.distance_estimator(z)).fold(1./0., f64::min)
+    pub fn distance_estimator(&self, z: &Vec3) -> Real {
+        self.0.iter().map(|obj| obj.distance_estimator(z)).fold(1./0., Real::min)
+    }
+
+    pub fn sample(&self, z: &Vec3) -> Sample {
+        self.0.iter().map(|obj| obj.sample(z)).fold(Sample::new(1./0.), Sample::closest)
+    }
+
+    pub fn distance_dual(&self, z: &Vec3<Dual3>) -> Dual3 {
+        self.0.iter().map(|obj| obj.distance_dual(z)).fold(Dual3::constant(1./0.), Scalar::min)
+    }
+
+    pub fn distance_packet(&self, z: &Vec3<Packet>) -> Packet {
+        self.0.iter().map(|obj| obj.distance_packet(z)).fold(Packet::splat(1./0.), Scalar::min)
+    }
+
+    pub fn normal(&self, z: &Vec3) -> Vec3 {
+        self.distance_dual(&Dual3::variables(z)).gradient().normalized()
     }
 
     pub fn add<O: Object + 'static>(&mut self, obj: O) {
@@ -16,29 +38,233 @@ impl Scene {
     }
 }
 
+// Everything an object knows about a point, not just the distance.
+// trap is the orbit trap value of fractals that have one set up
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Sample {
+    pub distance: Real,
+    pub trap: Option<Real>
+}
+
+impl Sample {
+    pub fn new(distance: Real) -> Self {
+        Self {distance, trap: None}
+    }
+
+    pub fn closest(self, other: Self) -> Self {
+        if self.distance <= other.distance { self } else { other }
+    }
+
+    pub fn farthest(self, other: Self) -> Self {
+        if self.distance >= other.distance { self } else { other }
+    }
+
+    // Keeps the extra data while transforming the distance
+    pub fn map<F: Fn(Real) -> Real>(self, f: F) -> Self {
+        Self {distance: f(self.distance), ..self}
+    }
+}
+
 pub trait Object {
-    fn distance_estimator(&self, z: &Vec3) -> f64;
+    fn distance_estimator(&self, z: &Vec3) -> Real;
+
+    fn sample(&self, z: &Vec3) -> Sample {
+        Sample::new(self.distance_estimator(z))
+    }
+
+    // The distance together with its gradient, for normals.
+    // Falls back to central differences, so override it to get exact
+    // gradients when the distance is built from Scalar operations.
+    // Every object in the crate does
+    fn distance_dual(&self, z: &Vec3<Dual3>) -> Dual3 {
+        let p = Dual3::values(z);
+        Dual3::through(z, self.distance_estimator(&p), &central_differences(|q| self.distance_estimator(q), &p))
+    }
+
+    // Distances to a packet of points, one per lane, for the packet marcher.
+    // Falls back to a distance_estimator call per lane, so override it when
+    // the distance is built from Scalar operations. The marcher parks rays
+    // that are done at NaN, which the fallback skips
+    fn distance_packet(&self, z: &Vec3<Packet>) -> Packet {
+        Packet::from_fn(|i| {
+            let p = z.extract(i);
+            if p.x.is_nan() { Real::NAN } else { self.distance_estimator(&p) }
+        })
+    }
+
+    fn normal(&self, z: &Vec3) -> Vec3 {
+        self.distance_dual(&Dual3::variables(z)).gradient().normalized()
+    }
+}
+
+// Gradient of f at p, for when nothing better is known. Six evaluations
+pub(crate) fn central_differences<F: Fn(&Vec3) -> Real>(f: F, p: &Vec3) -> Vec3 {
+    let h = Real::EPSILON.cbrt();
+    let diff = |e: Vec3| (f(&(*p + e)) - f(&(*p - e))) / (2.0 * h);
+    vec3!(diff(vec3!(h, 0, 0)), diff(vec3!(0, h, 0)), diff(vec3!(0, 0, h)))
 }
 
 pub struct Union<T: Object, U: Object>(pub T, pub U);
 
 impl<T: Object, U: Object> Object for Union<T, U> {
-    fn distance_estimator(&self, z: &Vec3) -> f64 {
+    fn distance_estimator(&self, z: &Vec3) -> Real {
         self.0.distance_estimator(z).min(self.1.distance_estimator(z))
     }
+
+    fn distance_dual(&self, z: &Vec3<Dual3>) -> Dual3 {
+        self.0.distance_dual(z).min(self.1.distance_dual(z))
+    }
+
+    fn distance_packet(&self, z: &Vec3<Packet>) -> Packet {
+        self.0.distance_packet(z).min(self.1.distance_packet(z))
+    }
+
+    fn sample(&self, z: &Vec3) -> Sample {
+        self.0.sample(z).closest(self.1.sample(z))
+    }
 }
 pub struct Subtract<T: Object, U: Object>(pub T, pub U);
 
 impl<T: Object, U: Object> Object for Subtract<T, U> {
-    fn distance_estimator(&self, z: &Vec3) -> f64 {
+    fn distance_estimator(&self, z: &Vec3) -> Real {
         self.0.distance_estimator(z).max(-self.1.distance_estimator(z))
     }
+
+    fn distance_dual(&self, z: &Vec3<Dual3>) -> Dual3 {
+        self.0.distance_dual(z).max(-self.1.distance_dual(z))
+    }
+
+    fn distance_packet(&self, z: &Vec3<Packet>) -> Packet {
+        self.0.distance_packet(z).max(-self.1.distance_packet(z))
+    }
+
+    fn sample(&self, z: &Vec3) -> Sample {
+        self.0.sample(z).farthest(self.1.sample(z).map(|d| -d))
+    }
 }
 
 pub struct Intersect<T: Object, U: Object>(pub T, pub U);
 
 impl<T: Object, U: Object> Object for Intersect<T, U> {
-    fn distance_estimator(&self, z: &Vec3) -> f64 {
+    fn distance_estimator(&self, z: &Vec3) -> Real {
         self.0.distance_estimator(z).max(self.1.distance_estimator(z))
     }
-}
\ No newline at end of file
+
+    fn distance_dual(&self, z: &Vec3<Dual3>) -> Dual3 {
+        self.0.distance_dual(z).max(self.1.distance_dual(z))
+    }
+
+    fn distance_packet(&self, z: &Vec3<Packet>) -> Packet {
+        self.0.distance_packet(z).max(self.1.distance_packet(z))
+    }
+
+    fn sample(&self, z: &Vec3) -> Sample {
+        self.0.sample(z).farthest(self.1.sample(z))
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::linalg::{Quat, LANES};
+    use crate::linalg::approx::EPSILON;
+    use crate::objects::{Sphere, Cube, Round, Rotate};
+
+    // Hides an object's own distance_dual, leaving central differences
+    struct Numeric<'a, T: Object>(&'a T);
+
+    impl<'a, T: Object> Object for Numeric<'a, T> {
+        fn distance_estimator(&self, z: &Vec3) -> Real {
+            self.0.distance_estimator(z)
+        }
+    }
+
+    // The exact gradient at p should match central differences, which are
+    // only good to about 1e-5 even in f64
+    pub(crate) fn check_gradient<T: Object>(obj: &T, p: &Vec3) {
+        let exact = obj.distance_dual(&Dual3::variables(p));
+        let numeric = Numeric(obj).distance_dual(&Dual3::variables(p));
+        assert!((exact.value - obj.distance_estimator(p)).abs() <= EPSILON, "{} at {:?}", exact.value, p);
+        assert!((exact.gradient() - numeric.gradient()).len() < EPSILON.max(1e-5), "{:?} != {:?} at {:?}", exact.gradient(), numeric.gradient(), p);
+    }
+
+    // Only knows its distance, so gradients go through central differences
+    struct Slab;
+
+    impl Object for Slab {
+        fn distance_estimator(&self, z: &Vec3) -> Real {
+            z.y.abs() - 0.5
+        }
+    }
+
+    fn close(a: &Vec3, b: &Vec3, tolerance: Real) -> bool {
+        (*a - *b).len() < tolerance
+    }
+
+    #[test]
+    fn exact_normals() {
+        let p = vec3!(1, 2, 2);
+        assert!(close(&Sphere::new(1.0).normal(&p), &(p / 3.0), 1e-15));
+        let carved = Subtract(Cube::new(&vec3!(1)), Sphere::new(1.2));
+        // inside the sphere's hole the surface faces the center
+        let d = carved.distance_dual(&Dual3::variables(&vec3!(0.9, 0.1, 0.1)));
+        assert_eq!(d.value, carved.distance_estimator(&vec3!(0.9, 0.1, 0.1)));
+        assert!(close(&d.gradient(), &-vec3!(0.9, 0.1, 0.1).normalized(), 1e-15));
+    }
+
+    #[test]
+    fn cube_face_normals() {
+        let cube = Cube::new(&vec3!(1));
+        // on the face and inside, where the outside part has zero length
+        assert!(close(&cube.normal(&vec3!(1, 0.2, 0.3)), &vec3!(1, 0, 0), 1e-15));
+        assert!(close(&cube.normal(&vec3!(0.5, 0.1, 0.2)), &vec3!(1, 0, 0), 1e-15));
+        let mut scene = Scene::new();
+        scene.add(Subtract(Cube::new(&vec3!(0.75)), Sphere::new(1.0)));
+        assert!(close(&scene.normal(&vec3!(0.7499, 0.7, 0.7)), &vec3!(1, 0, 0), 1e-15));
+    }
+
+    #[test]
+    fn rotated_normals() {
+        let turned = Rotate(Round(Cube::new(&vec3!(2, 0.5, 0.5)), 0.1), Quat::from_axis_angle(&vec3!(0, 0, 1), 0.3));
+        let (s, c) = (0.3 as Real).sin_cos();
+        // the top face of the box tilts with the rotation
+        let top = vec3!(-s, c, 0);
+        assert!(close(&turned.normal(&(top * 2.0)), &top, EPSILON));
+    }
+
+    #[test]
+    fn finite_difference_fallback() {
+        assert!(close(&Slab.normal(&vec3!(3, 2, 1)), &vec3!(0, 1, 0), 1e-9));
+        // the chain rule carries the fallback through a rotation
+        let turned = Rotate(Slab, Quat::from_axis_angle(&vec3!(1, 0, 0), 0.5));
+        let (s, c) = (0.5 as Real).sin_cos();
+        assert!(close(&turned.normal(&vec3!(0, c, s)), &vec3!(0, c, s), 1e-9));
+    }
+
+    #[test]
+    fn fallback_skips_parked_lanes() {
+        struct Counted(std::cell::Cell<usize>);
+        impl Object for Counted {
+            fn distance_estimator(&self, z: &Vec3) -> Real {
+                self.0.set(self.0.get() + 1);
+                Slab.distance_estimator(z)
+            }
+        }
+        let counted = Counted(std::cell::Cell::new(0));
+        let mut z = Vec3::new(Packet::splat(0.0), Packet::from_fn(|i| i as Real), Packet::splat(0.0));
+        z.x.0[1] = Real::NAN;
+        let d = counted.distance_packet(&z);
+        assert_eq!(counted.0.get(), LANES - 1);
+        assert!(d.0[1].is_nan());
+        assert_eq!(d.0[2], 1.5);
+    }
+
+    #[test]
+    fn scene_normal() {
+        let mut scene = Scene::new();
+        scene.add(Sphere::new(1.0));
+        scene.add(Slab);
+        assert!(close(&scene.normal(&vec3!(1.2, 1.6, 0)), &vec3!(0.6, 0.8, 0), 1e-15));
+        assert!(close(&scene.normal(&vec3!(5, 0.6, 0)), &vec3!(0, 1, 0), 1e-9));
+    }
+}
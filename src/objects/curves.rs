@@ -0,0 +1,454 @@
+use crate::linalg::{Real, Scalar, Dual3, Vec2, Vec3};
+use crate::objects::base::Object;
+use crate::objects::shapes2d::Object2D;
+
+// Any simple polygon, convex or not, given by its vertices in order.
+// The sign comes from the parity of edge crossings, so the direction
+// doesn't matter
+pub struct Polygon {
+    vertices: Vec<Vec2>
+}
+
+impl Polygon {
+    pub fn new(vertices: Vec<Vec2>) -> Self {
+        assert!(vertices.len() >= 3, "a polygon needs at least three vertices");
+        Self {vertices}
+    }
+}
+
+impl Polygon {
+    fn distance<S: Scalar>(&self, p: &Vec2<S>) -> S {
+        let v: Vec<Vec2<S>> = self.vertices.iter().map(Vec2::lift).collect();
+        let first = *p - v[0];
+        let mut d = first.dot(&first);
+        let (zero, one) = (S::from_real(0.0), S::from_real(1.0));
+        let mut s = one;
+        let mut j = v.len() - 1;
+        for i in 0..v.len() {
+            let e = v[j] - v[i];
+            let w = *p - v[i];
+            let b = w - e * clamp01(w.dot(&e) / e.dot(&e));
+            d = d.min(b.dot(&b));
+            // flip the sign each time a ray towards +x crosses an edge, with
+            // the conditions as ones and zeros so packets can disagree
+            let a = p.y.select_lt(v[i].y, zero, one);
+            let b = p.y.select_lt(v[j].y, one, zero);
+            let c = (e.y * w.x).select_lt(e.x * w.y, one, zero);
+            let crossed = a * b * c + (one - a) * (one - b) * (one - c);
+            s *= one - S::from_real(2.0) * crossed;
+            j = i;
+        }
+        s * d.sqrt()
+    }
+}
+
+impl Object2D for Polygon {
+    fn distance_estimator(&self, p: &Vec2) -> Real {
+        self.distance(p)
+    }
+
+    fn distance_dual(&self, p: &Vec2<Dual3>) -> Dual3 {
+        self.distance(p)
+    }
+}
+
+fn clamp01<S: Scalar>(t: S) -> S {
+    t.max(S::from_real(0.0)).min(S::from_real(1.0))
+}
+
+fn segment2<S: Scalar>(p: &Vec2<S>, a: &Vec2<S>, b: &Vec2<S>) -> S {
+    let pa = *p - *a;
+    let ba = *b - *a;
+    (pa - ba * clamp01(pa.dot(&ba) / ba.dot(&ba))).len()
+}
+
+// Whichever of the points is nearest to p
+fn nearest(p: &Vec3, points: impl IntoIterator<Item = Vec3>) -> Vec3 {
+    points.into_iter()
+        .map(|q| ((q - *p).len(), q))
+        .fold((1./0., *p), |best, next| if next.0 < best.0 { next } else { best }).1
+}
+
+// The point on the segment from a to b closest to p
+fn segment3(p: &Vec3, a: &Vec3, b: &Vec3) -> Vec3 {
+    let pa = *p - *a;
+    let ba = *b - *a;
+    *a + ba * (pa.dot(&ba) / ba.dot(&ba)).clamp(0.0, 1.0)
+}
+
+// Open chain of line segments, thickened to a stroke
+pub struct Polyline {
+    points: Vec<Vec2>,
+    thickness: Real
+}
+
+impl Polyline {
+    pub fn new(points: Vec<Vec2>, thickness: Real) -> Self {
+        assert!(points.len() >= 2, "a polyline needs at least two points");
+        Self {points, thickness}
+    }
+}
+
+impl Polyline {
+    fn distance<S: Scalar>(&self, p: &Vec2<S>) -> S {
+        self.points.windows(2)
+            .map(|s| segment2(p, &s[0].lift(), &s[1].lift()))
+            .fold(S::from_real(1./0.), S::min) - S::from_real(self.thickness)
+    }
+}
+
+impl Object2D for Polyline {
+    fn distance_estimator(&self, p: &Vec2) -> Real {
+        self.distance(p)
+    }
+
+    fn distance_dual(&self, p: &Vec2<Dual3>) -> Dual3 {
+        self.distance(p)
+    }
+}
+
+// Candidate curve parameters for the point closest to p on the quadratic
+// Bezier a, b, c. Setting the derivative of the squared distance to zero gives
+// a cubic in t, which only needs the dot products of
+// A = b - a, B = a - 2b + c and P = a - p, so this works in any dimension
+fn bezier_roots(aa: Real, ab: Real, bb: Real, pa: Real, pb: Real) -> Vec<Real> {
+    let kk = 1.0 / bb;
+    let kx = kk * ab;
+    let ky = kk * (2.0 * aa + pb) / 3.0;
+    let kz = kk * pa;
+    let p = ky - kx * kx;
+    let q = kx * (2.0 * kx * kx - 3.0 * ky) + kz;
+    let h = q * q + 4.0 * p * p * p;
+    let roots = if h >= 0.0 {
+        // one real root
+        let h = h.sqrt();
+        let u = ((h - q) / 2.0).cbrt();
+        let v = ((-h - q) / 2.0).cbrt();
+        vec![u + v - kx]
+    } else {
+        // three real roots, the middle one is never the closest
+        let z = (-p).sqrt();
+        let angle = (q / (p * z * 2.0)).acos() / 3.0;
+        let m = angle.cos();
+        let n = angle.sin() * Real::sqrt(3.0);
+        vec![(m + m) * z - kx, (-n - m) * z - kx]
+    };
+    roots.into_iter().map(|t| t.clamp(0.0, 1.0)).collect()
+}
+
+// Quadratic Bezier curve from a to c pulled towards b, thickened to a stroke
+pub struct QuadraticBezier {
+    a: Vec2,
+    b: Vec2,
+    c: Vec2,
+    thickness: Real
+}
+
+impl QuadraticBezier {
+    pub fn new(a: &Vec2, b: &Vec2, c: &Vec2, thickness: Real) -> Self {
+        Self {a: *a, b: *b, c: *c, thickness}
+    }
+
+    pub fn at(&self, t: Real) -> Vec2 {
+        self.a * (1.0 - t) * (1.0 - t) + self.b * 2.0 * t * (1.0 - t) + self.c * t * t
+    }
+
+    // The point on the curve closest to p
+    fn closest(&self, p: &Vec2) -> Vec2 {
+        let aa = self.b - self.a;
+        let bb = self.a - self.b * 2.0 + self.c;
+        let pp = self.a - *p;
+        let candidates = if bb.dot(&bb) < 1e-12 {
+            // the control point is on the line, so it's just a segment
+            let ca = self.c - self.a;
+            vec![(-pp.dot(&ca) / ca.dot(&ca)).clamp(0.0, 1.0)]
+        } else {
+            bezier_roots(aa.dot(&aa), aa.dot(&bb), bb.dot(&bb), pp.dot(&aa), pp.dot(&bb))
+        };
+        candidates.into_iter()
+            .map(|t| self.at(t))
+            .fold(self.a, |best, q| if (q - *p).len() < (best - *p).len() { q } else { best })
+    }
+}
+
+impl Object2D for QuadraticBezier {
+    fn distance_estimator(&self, p: &Vec2) -> Real {
+        (self.closest(p) - *p).len() - self.thickness
+    }
+
+    // The closest point doesn't move to first order as p does, since the
+    // distance is stationary there
+    fn distance_dual(&self, p: &Vec2<Dual3>) -> Dual3 {
+        let q = self.closest(&vec2!(p.x.value, p.y.value));
+        (*p - q.lift()).len() - Dual3::constant(self.thickness)
+    }
+}
+
+// Curves in space, known by the point on them closest to any other and
+// measured by their unsigned distance, which defaults to how far that is.
+// Wrap them in a Tube to get something to render
+pub trait Curve {
+    fn closest(&self, p: &Vec3) -> Vec3;
+
+    fn distance(&self, p: &Vec3) -> Real {
+        (self.closest(p) - *p).len()
+    }
+}
+
+pub struct Polyline3 {
+    points: Vec<Vec3>
+}
+
+impl Polyline3 {
+    pub fn new(points: Vec<Vec3>) -> Self {
+        assert!(points.len() >= 2, "a polyline needs at least two points");
+        Self {points}
+    }
+}
+
+impl Curve for Polyline3 {
+    fn closest(&self, p: &Vec3) -> Vec3 {
+        nearest(p, self.points.windows(2).map(|s| segment3(p, &s[0], &s[1])))
+    }
+}
+
+pub struct QuadraticBezier3 {
+    a: Vec3,
+    b: Vec3,
+    c: Vec3
+}
+
+impl QuadraticBezier3 {
+    pub fn new(a: &Vec3, b: &Vec3, c: &Vec3) -> Self {
+        Self {a: *a, b: *b, c: *c}
+    }
+
+    pub fn at(&self, t: Real) -> Vec3 {
+        self.a * (1.0 - t) * (1.0 - t) + self.b * 2.0 * t * (1.0 - t) + self.c * t * t
+    }
+}
+
+impl Curve for QuadraticBezier3 {
+    fn closest(&self, p: &Vec3) -> Vec3 {
+        let aa = self.b - self.a;
+        let bb = self.a - self.b * 2.0 + self.c;
+        let pp = self.a - *p;
+        if bb.dot(&bb) < 1e-12 {
+            return segment3(p, &self.a, &self.c);
+        }
+        let roots = bezier_roots(aa.dot(&aa), aa.dot(&bb), bb.dot(&bb), pp.dot(&aa), pp.dot(&bb));
+        nearest(p, roots.into_iter().map(|t| self.at(t)))
+    }
+}
+
+// Uniform Catmull-Rom spline passing through all the points.
+// There's no closed form for the closest point on a cubic, so each segment
+// is cut into spans and the distance bounded from below on each of them,
+// splitting the spans that might hold the closest point until the bound is
+// within TOLERANCE of it. The result never overestimates the true distance
+pub struct CatmullRom {
+    points: Vec<Vec3>
+}
+
+const SAMPLES: usize = 16;
+const TOLERANCE: Real = 1e-6;
+const MAX_DEPTH: u32 = 24;
+
+impl CatmullRom {
+    pub fn new(points: Vec<Vec3>) -> Self {
+        assert!(points.len() >= 2, "a spline needs at least two points");
+        // repeat the ends so the curve reaches the first and last points
+        let mut padded = vec![points[0]];
+        padded.extend(points.iter());
+        padded.push(points[points.len() - 1]);
+        Self {points: padded}
+    }
+
+    // Position and acceleration on a segment
+    fn segment(p: &[Vec3], t: Real) -> (Vec3, Vec3) {
+        let c1 = (p[2] - p[0]) * 0.5;
+        let c2 = p[0] - p[1] * 2.5 + p[2] * 2.0 - p[3] * 0.5;
+        let c3 = (p[3] - p[0]) * 0.5 + (p[1] - p[2]) * 1.5;
+        (
+            p[1] + c1 * t + c2 * t * t + c3 * t * t * t,
+            c2 * 2.0 + c3 * 6.0 * t
+        )
+    }
+
+    pub fn at(&self, t: Real) -> Vec3 {
+        let segments = self.points.len() - 3;
+        let i = (t.max(0.0) as usize).min(segments - 1);
+        Self::segment(&self.points[i..i + 4], t - i as Real).0
+    }
+
+    // Lower bound on the distance from p to the curve between t0 and t1,
+    // moving best to the nearest point seen so far if one is nearer.
+    // The span strays from its chord by at most dt^2 / 8 times its largest
+    // acceleration, which is at one of the ends since it's linear in t
+    fn span_bound(segment: &[Vec3], p: &Vec3, t0: Real, t1: Real, best: &mut Vec3, depth: u32) -> Real {
+        let (a, acc0) = Self::segment(segment, t0);
+        let (b, acc1) = Self::segment(segment, t1);
+        *best = nearest(p, [*best, a, b]);
+        let dt = t1 - t0;
+        let bound = (segment3(p, &a, &b) - *p).len() - dt * dt / 8.0 * acc0.len().max(acc1.len());
+        // a NaN from a span of zero length can't hold anything closer either
+        if bound.is_nan() || bound >= (*best - *p).len() - TOLERANCE || depth == MAX_DEPTH {
+            return bound;
+        }
+        let mid = (t0 + t1) / 2.0;
+        let left = Self::span_bound(segment, p, t0, mid, best, depth + 1);
+        let right = Self::span_bound(segment, p, mid, t1, best, depth + 1);
+        left.min(right)
+    }
+
+    // The lower bound on the distance, along with the nearest point found
+    fn search(&self, p: &Vec3) -> (Real, Vec3) {
+        let spans = || (0..SAMPLES).map(|i| (i as Real / SAMPLES as Real, (i + 1) as Real / SAMPLES as Real));
+        // start from the nearest sample so most spans are ruled out at once
+        let mut best = nearest(p, self.points.windows(4)
+            .flat_map(|segment| spans().map(move |(t, _)| Self::segment(segment, t).0)));
+        let mut lower = 1./0.;
+        for segment in self.points.windows(4) {
+            for (t0, t1) in spans() {
+                lower = Real::min(lower, Self::span_bound(segment, p, t0, t1, &mut best, 0));
+            }
+        }
+        (lower.min((best - *p).len()).max(0.0), best)
+    }
+}
+
+impl Curve for CatmullRom {
+    fn closest(&self, p: &Vec3) -> Vec3 {
+        self.search(p).1
+    }
+
+    fn distance(&self, p: &Vec3) -> Real {
+        self.search(p).0
+    }
+}
+
+// A pipe of the given radius following the curve
+pub struct Tube<C: Curve>(pub C, pub Real);
+
+impl<C: Curve> Object for Tube<C> {
+    fn distance_estimator(&self, z: &Vec3) -> Real {
+        self.0.distance(z) - self.1
+    }
+
+    // The distance grows straight away from the closest point
+    fn distance_dual(&self, z: &Vec3<Dual3>) -> Dual3 {
+        let p = Dual3::values(z);
+        let away = (*z - self.0.closest(&p).lift()).len();
+        Dual3::new(self.distance_estimator(&p), away.derivative)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::objects::Extrude;
+    use crate::objects::base::tests::check_gradient;
+
+    fn close(a: Real, b: Real) -> bool {
+        (a - b).abs() < 1e-9
+    }
+
+    #[test]
+    fn concave_polygon() {
+        // an L shape
+        let l = Polygon::new(vec![
+            vec2!(0, 0), vec2!(2, 0), vec2!(2, 1), vec2!(1, 1), vec2!(1, 2), vec2!(0, 2)
+        ]);
+        assert!(close(l.distance_estimator(&vec2!(0.5, 0.5)), -0.5));
+        assert!(close(l.distance_estimator(&vec2!(1.5, 1.5)), 0.5));
+        assert!(close(l.distance_estimator(&vec2!(3, 0.5)), 1.0));
+        // reversing the winding gives the same field
+        let reversed = Polygon::new(l.vertices.iter().rev().cloned().collect());
+        assert!(close(reversed.distance_estimator(&vec2!(0.5, 0.5)), -0.5));
+    }
+
+    #[test]
+    fn polyline() {
+        let line = Polyline::new(vec![vec2!(0, 0), vec2!(1, 0), vec2!(1, 1)], 0.1);
+        assert!(close(line.distance_estimator(&vec2!(0.5, 1)), 0.4));
+        assert!(close(line.distance_estimator(&vec2!(2, 0.5)), 0.9));
+    }
+
+    #[test]
+    fn bezier_matches_brute_force() {
+        let curve = QuadraticBezier::new(&vec2!(-1, 0), &vec2!(0, 2), &vec2!(1, 0), 0.0);
+        for p in [vec2!(0, 0), vec2!(0, 3), vec2!(2, 2), vec2!(-0.3, 0.9), vec2!(5, -1)].iter() {
+            let brute = (0..=10000)
+                .map(|i| (curve.at(i as Real / 10000.0) - *p).len())
+                .fold(1./0., Real::min);
+            assert!((curve.distance_estimator(p) - brute).abs() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn straight_bezier_is_a_segment() {
+        let curve = QuadraticBezier3::new(&vec3!(0), &vec3!(1, 0, 0), &vec3!(2, 0, 0));
+        assert!(close(curve.distance(&vec3!(1, 1, 0)), 1.0));
+    }
+
+    #[test]
+    fn catmull_rom_passes_through_points() {
+        let points = vec![vec3!(0), vec3!(1, 1, 0), vec3!(2, 0, 1), vec3!(3, 1, 1)];
+        let spline = CatmullRom::new(points.clone());
+        for (i, p) in points.iter().enumerate() {
+            assert!((spline.at(i as Real) - *p).len() < 1e-12);
+            assert!(close(spline.distance(p), 0.0));
+        }
+        let tube = Tube(spline, 0.25);
+        assert!(close(tube.distance_estimator(&vec3!(1, 1, 0)), -0.25));
+    }
+
+    #[test]
+    fn catmull_rom_never_overestimates() {
+        let spline = CatmullRom::new(vec![vec3!(0), vec3!(1, 3, 0), vec3!(1.5, -2, 1), vec3!(3, 1, 0)]);
+        for p in [vec3!(0.8, 1, 0), vec3!(1.2, 0, 0.4), vec3!(2, 2, -1), vec3!(-1, 0, 0)].iter() {
+            let brute = (0..=30000)
+                .map(|i| (spline.at(i as Real / 10000.0) - *p).len())
+                .fold(1./0., Real::min);
+            let d = spline.distance(p);
+            assert!(d <= brute && d > brute - 1e-5);
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "at least two points")]
+    fn catmull_rom_needs_two_points() {
+        CatmullRom::new(vec![vec3!(1)]);
+    }
+
+    #[test]
+    #[should_panic(expected = "at least three vertices")]
+    fn polygon_needs_three_vertices() {
+        Polygon::new(vec![]);
+    }
+
+    #[test]
+    fn exact_gradients() {
+        let p = vec3!(0.9, 0.7, 0.4);
+        let l = Polygon::new(vec![
+            vec2!(0, 0), vec2!(2, 0), vec2!(2, 1), vec2!(1, 1), vec2!(1, 2), vec2!(0, 2)
+        ]);
+        check_gradient(&Extrude(l, 1.0), &p);
+        check_gradient(&Extrude(Polyline::new(vec![vec2!(0), vec2!(1, 1), vec2!(2, 0)], 0.1), 1.0), &p);
+        check_gradient(&Extrude(QuadraticBezier::new(&vec2!(0), &vec2!(1, 2), &vec2!(2, 0), 0.1), 1.0), &p);
+        check_gradient(&Tube(Polyline3::new(vec![vec3!(0), vec3!(1, 1, 0), vec3!(1, 1, 1)]), 0.1), &p);
+        check_gradient(&Tube(QuadraticBezier3::new(&vec3!(0), &vec3!(1, 2, 0), &vec3!(2, 0, 1)), 0.1), &p);
+    }
+
+    #[test]
+    fn catmull_rom_gradient_points_away_from_the_curve() {
+        // the distance is only a bound, so compare against a dense sampling
+        // rather than central differences
+        let points = vec![vec3!(0), vec3!(1, 1, 0), vec3!(2, 0, 1), vec3!(3, 1, 1)];
+        let spline = CatmullRom::new(points.clone());
+        let p = vec3!(0.9, 0.7, 0.4);
+        let q = nearest(&p, (0..=30000).map(|i| spline.at(i as Real / 10000.0)));
+        let gradient = Tube(CatmullRom::new(points), 0.1).distance_dual(&Dual3::variables(&p)).gradient();
+        assert!((gradient - (p - q).normalized()).len() < 1e-3);
+    }
+}
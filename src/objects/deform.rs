@@ -0,0 +1,286 @@
+use crate::linalg::{Real, Scalar, Dual3, Packet, Vec2, Vec3};
+use crate::objects::base::{Object, Sample};
+
+// Domain deformations warp the query point before handing it to the inner object.
+// A warped field is no longer a true distance, so every wrapper divides by a
+// Lipschitz bound of the warp to keep the marcher from overstepping.
+// The bounds for twist, bend and taper depend on how far from the axis the
+// object reaches, which is what the `radius` parameters are for.
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Axis {
+    X,
+    Y,
+    Z
+}
+
+impl Axis {
+    // Splits a point into the coordinate along the axis and the two coordinates
+    // across it, in cyclic order so the plane keeps its handedness
+    pub fn split<T: Scalar>(&self, p: &Vec3<T>) -> (T, Vec2<T>) {
+        match self {
+            Axis::X => (p.x, p.yz()),
+            Axis::Y => (p.y, p.zx()),
+            Axis::Z => (p.z, p.xy())
+        }
+    }
+
+    pub fn join<T: Scalar>(&self, along: T, across: &Vec2<T>) -> Vec3<T> {
+        match self {
+            Axis::X => Vec3::new(along, across.x, across.y),
+            Axis::Y => Vec3::new(across.y, along, across.x),
+            Axis::Z => Vec3::new(across.x, across.y, along)
+        }
+    }
+
+    pub fn get<T: Scalar>(&self, p: &Vec3<T>) -> T {
+        self.split(p).0
+    }
+
+    pub fn set<T: Scalar>(&self, p: &Vec3<T>, value: T) -> Vec3<T> {
+        self.join(value, &self.split(p).1)
+    }
+}
+
+fn rotate<S: Scalar>(v: &Vec2<S>, angle: S) -> Vec2<S> {
+    let (s, c) = (angle.sin(), angle.cos());
+    Vec2::new(c * v.x - s * v.y, s * v.x + c * v.y)
+}
+
+// Largest singular value of the 2x2 shear [[1, k], [0, 1]]
+fn shear_bound(k: Real) -> Real {
+    (k.abs() + (k * k + 4.0).sqrt()) / 2.0
+}
+
+// The Object impl of a wrapper that warps the query point with its warp
+// method and hands it to the inner object in field $inner, dividing by the
+// Lipschitz bound in field $lipschitz if the warp stretches space
+macro_rules! impl_warped_object {
+    ($type:ident, $inner:tt $(/ $lipschitz:ident)?) => {
+        impl<T: Object> Object for $type<T> {
+            fn distance_estimator(&self, z: &Vec3) -> Real {
+                self.$inner.distance_estimator(&self.warp(z)) $(/ self.$lipschitz)?
+            }
+
+            fn sample(&self, z: &Vec3) -> Sample {
+                self.$inner.sample(&self.warp(z)) $(.map(|d| d / self.$lipschitz))?
+            }
+
+            fn distance_dual(&self, z: &Vec3<Dual3>) -> Dual3 {
+                self.$inner.distance_dual(&self.warp(z)) $(/ Dual3::constant(self.$lipschitz))?
+            }
+
+            fn distance_packet(&self, z: &Vec3<Packet>) -> Packet {
+                self.$inner.distance_packet(&self.warp(z)) $(/ Packet::splat(self.$lipschitz))?
+            }
+        }
+    };
+}
+
+pub struct Twist<T: Object> {
+    obj: T,
+    axis: Axis,
+    rate: Real,
+    lipschitz: Real
+}
+
+impl<T: Object> Twist<T> {
+    // rate is in radians per unit along the axis
+    pub fn new(obj: T, axis: Axis, rate: Real, radius: Real) -> Self {
+        Self {obj, axis, rate, lipschitz: shear_bound(rate * radius)}
+    }
+
+    fn warp<S: Scalar>(&self, z: &Vec3<S>) -> Vec3<S> {
+        let (h, across) = self.axis.split(z);
+        self.axis.join(h, &rotate(&across, S::from_real(-self.rate) * h))
+    }
+}
+
+impl_warped_object!(Twist, obj / lipschitz);
+
+// The cheap bend: rotates the xy-plane by an angle proportional to x,
+// curling an object lying along the x axis upwards
+pub struct Bend<T: Object> {
+    obj: T,
+    rate: Real,
+    lipschitz: Real
+}
+
+impl<T: Object> Bend<T> {
+    pub fn new(obj: T, rate: Real, radius: Real) -> Self {
+        Self {obj, rate, lipschitz: shear_bound(rate * radius)}
+    }
+
+    fn warp<S: Scalar>(&self, z: &Vec3<S>) -> Vec3<S> {
+        let q = rotate(&z.xy(), S::from_real(self.rate) * z.x);
+        Vec3::new(q.x, q.y, z.z)
+    }
+}
+
+impl_warped_object!(Bend, obj / lipschitz);
+
+// Scales the cross-section by 1 + rate * h along the axis
+pub struct Taper<T: Object> {
+    obj: T,
+    axis: Axis,
+    rate: Real,
+    lipschitz: Real
+}
+
+impl<T: Object> Taper<T> {
+    // Here radius bounds the whole object around the origin, along the axis
+    // too, and the taper mustn't pinch inside it
+    pub fn new(obj: T, axis: Axis, rate: Real, radius: Real) -> Self {
+        let thinnest = 1.0 - rate.abs() * radius;
+        assert!(thinnest > 0.0, "a taper with |rate| * radius >= 1 pinches the object");
+        Self {obj, axis, rate, lipschitz: taper_bound(rate * radius / thinnest, 1.0 / thinnest)}
+    }
+
+    fn warp<S: Scalar>(&self, z: &Vec3<S>) -> Vec3<S> {
+        let (h, across) = self.axis.split(z);
+        // keep the scale away from zero so the pinch point doesn't blow up
+        let scale = (S::from_real(1.0) + S::from_real(self.rate) * h).max(S::from_real(1e-3));
+        self.axis.join(h, &(across / scale))
+    }
+}
+
+// Largest singular value of [[1, 0], [k, m]], the taper's Jacobian in the
+// plane through the axis. k is how fast the cross-section grows and m how
+// much it's shrunk, both at their worst within the radius
+fn taper_bound(k: Real, m: Real) -> Real {
+    let t = 1.0 + k * k + m * m;
+    ((t + (t * t - 4.0 * m * m).sqrt()) / 2.0).sqrt()
+}
+
+impl_warped_object!(Taper, obj / lipschitz);
+
+// Moves points along one axis proportionally to their coordinate on another.
+// Shearing is linear, so unlike the others the bound here is exact
+pub struct Shear<T: Object> {
+    obj: T,
+    axis: Axis,
+    along: Axis,
+    factor: Real,
+    lipschitz: Real
+}
+
+impl<T: Object> Shear<T> {
+    // Shearing an axis along itself would scale it instead
+    pub fn new(obj: T, axis: Axis, along: Axis, factor: Real) -> Self {
+        assert!(axis != along, "a shear needs two different axes");
+        Self {obj, axis, along, factor, lipschitz: shear_bound(factor)}
+    }
+
+    fn warp<S: Scalar>(&self, z: &Vec3<S>) -> Vec3<S> {
+        self.axis.set(z, self.axis.get(z) - S::from_real(self.factor) * self.along.get(z))
+    }
+}
+
+impl_warped_object!(Shear, obj / lipschitz);
+
+#[cfg(test)]
+mod tests {
+    use crate::linalg::LANES;
+    use super::*;
+    use crate::linalg::approx::EPSILON;
+    use crate::objects::base::tests::check_gradient;
+    use crate::objects::{Sphere, Cube};
+
+    #[test]
+    fn axis_roundtrip() {
+        let p = vec3!(1, 2, 3);
+        for axis in [Axis::X, Axis::Y, Axis::Z].iter() {
+            let (h, across) = axis.split(&p);
+            assert_eq!(axis.join(h, &across), p);
+        }
+    }
+
+    #[test]
+    fn zero_deformation_is_identity() {
+        let p = vec3!(0.3, -1.2, 2.0);
+        let expected = Cube::new(&vec3!(1)).distance_estimator(&p);
+        assert_eq!(Twist::new(Cube::new(&vec3!(1)), Axis::Y, 0.0, 2.0).distance_estimator(&p), expected);
+        assert_eq!(Bend::new(Cube::new(&vec3!(1)), 0.0, 2.0).distance_estimator(&p), expected);
+        assert_eq!(Taper::new(Cube::new(&vec3!(1)), Axis::Y, 0.0, 2.0).distance_estimator(&p), expected);
+        assert_eq!(Shear::new(Cube::new(&vec3!(1)), Axis::X, Axis::Y, 0.0).distance_estimator(&p), expected);
+    }
+
+    #[test]
+    fn exact_gradients() {
+        let p = vec3!(0.4, 1.1, -0.3);
+        check_gradient(&Twist::new(Cube::new(&vec3!(0.5, 1.5, 0.5)), Axis::Y, 0.8, 1.0), &p);
+        check_gradient(&Bend::new(Cube::new(&vec3!(1.5, 0.3, 0.3)), 0.5, 2.0), &p);
+        check_gradient(&Taper::new(Cube::new(&vec3!(0.5, 1.5, 0.5)), Axis::Y, 0.3, 1.0), &p);
+        check_gradient(&Shear::new(Sphere::new(1.0), Axis::X, Axis::Y, 2.0), &p);
+    }
+
+    #[test]
+    fn packets_match_single_points() {
+        let points: [Vec3; LANES] = std::array::from_fn(|i| vec3!(0.4, 1.1, -0.3) * (i as Real * 0.5 - 1.0));
+        let packet = Vec3::gather(&points);
+        let objects: [Box<dyn Object>; 4] = [
+            Box::new(Twist::new(Cube::new(&vec3!(0.5, 1.5, 0.5)), Axis::Y, 0.8, 1.0)),
+            Box::new(Bend::new(Cube::new(&vec3!(1.5, 0.3, 0.3)), 0.5, 2.0)),
+            Box::new(Taper::new(Cube::new(&vec3!(0.5, 1.5, 0.5)), Axis::Y, 0.3, 1.7)),
+            Box::new(Shear::new(Sphere::new(1.0), Axis::X, Axis::Y, 2.0))
+        ];
+        for obj in objects.iter() {
+            let distances = obj.distance_packet(&packet);
+            for (i, p) in points.iter().enumerate() {
+                assert_approx_eq!(distances.0[i], obj.distance_estimator(p));
+            }
+        }
+    }
+
+    #[test]
+    fn taper_is_lipschitz_within_its_radius() {
+        let taper = Taper::new(Cube::new(&vec3!(0.5, 1, 0.5)), Axis::Y, 0.5, 1.25);
+        let n = 12;
+        let at = |i: i32, j: i32, k: i32| vec3!(i, j, k) * (2.5 / n as Real) - vec3!(1.25);
+        for i in 0..n {
+            for j in 0..n {
+                for k in 0..n {
+                    let p = at(i, j, k);
+                    for q in [at(i + 1, j, k), at(i, j + 1, k), at(i, j, k + 1), at(i + 1, j + 1, k + 1)].iter() {
+                        if p.len() > 1.25 || q.len() > 1.25 {
+                            continue;
+                        }
+                        let change = (taper.distance_estimator(&p) - taper.distance_estimator(q)).abs();
+                        assert!(change <= (p - *q).len() * (1.0 + EPSILON));
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "pinches the object")]
+    fn taper_rejects_a_pinch_inside_the_radius() {
+        Taper::new(Sphere::new(1.0), Axis::Y, 1.0, 1.0);
+    }
+
+    #[test]
+    #[should_panic(expected = "two different axes")]
+    fn shear_rejects_a_single_axis() {
+        Shear::new(Sphere::new(1.0), Axis::X, Axis::X, 0.5);
+    }
+
+    #[test]
+    fn twisting_a_sphere_changes_nothing_but_the_bound() {
+        // a sphere centered on the axis is rotationally symmetric
+        let twist = Twist::new(Sphere::new(1.0), Axis::Z, 1.5, 1.0);
+        let p = vec3!(0.5, 2.0, -0.7);
+        let expected = Sphere::new(1.0).distance_estimator(&p) / shear_bound(1.5);
+        assert!((twist.distance_estimator(&p) - expected).abs() < EPSILON);
+    }
+
+    #[test]
+    fn shear_never_overestimates() {
+        let shear = Shear::new(Sphere::new(1.0), Axis::X, Axis::Y, 2.0);
+        // the surface point of the sheared sphere
+        let surface = vec3!(2.0 * 0.6 + 0.8, 0.6, 0);
+        assert!(shear.distance_estimator(&surface).abs() < EPSILON);
+        let p = vec3!(5, 0, 0);
+        assert!(shear.distance_estimator(&p) <= (p - surface).len());
+    }
+}
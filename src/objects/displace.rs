@@ -0,0 +1,185 @@
+use std::path::Path;
+use image::{DynamicImage, GrayImage, ImageResult};
+use crate::linalg::{Real, Dual3, Vec2, Vec3};
+use crate::objects::base::{Object, Sample, central_differences};
+use crate::objects::deform::Axis;
+
+// The gradient falls back to central differences of the field alone.
+// Override it when the slope is known in closed form, like ImageField does
+pub trait ScalarField {
+    fn sample(&self, z: &Vec3) -> Real;
+
+    fn gradient(&self, z: &Vec3) -> Vec3 {
+        central_differences(|q| self.sample(q), z)
+    }
+}
+
+impl<F: Fn(&Vec3) -> Real> ScalarField for F {
+    fn sample(&self, z: &Vec3) -> Real {
+        self(z)
+    }
+}
+
+// Adds a scalar field to the distance of the inner object. Positive values
+// carve into the surface, negative values push it outwards.
+// The sum is not a distance anymore, so steps are multiplied by step_scale.
+// Something around 1 / (1 + steepest slope of the field) is a safe choice,
+// lower it if the render shows holes or fuzzy edges
+pub struct Displace<T: Object, F: ScalarField> {
+    obj: T,
+    field: F,
+    step_scale: Real
+}
+
+impl<T: Object, F: ScalarField> Displace<T, F> {
+    pub fn new(obj: T, field: F, step_scale: Real) -> Self {
+        Self {obj, field, step_scale}
+    }
+}
+
+impl<T: Object, F: ScalarField> Object for Displace<T, F> {
+    fn distance_estimator(&self, z: &Vec3) -> Real {
+        (self.obj.distance_estimator(z) + self.field.sample(z)) * self.step_scale
+    }
+
+    fn sample(&self, z: &Vec3) -> Sample {
+        let offset = self.field.sample(z);
+        self.obj.sample(z).map(|d| (d + offset) * self.step_scale)
+    }
+
+    fn distance_dual(&self, z: &Vec3<Dual3>) -> Dual3 {
+        let p = Dual3::values(z);
+        let offset = Dual3::through(z, self.field.sample(&p), &self.field.gradient(&p));
+        (self.obj.distance_dual(z) + offset) * Dual3::constant(self.step_scale)
+    }
+}
+
+// A grayscale image sampled with bilinear filtering, repeating in both
+// directions. Black is 0 and white is 1
+pub struct Texture {
+    image: GrayImage
+}
+
+impl Texture {
+    pub fn new(image: &DynamicImage) -> Self {
+        let image = image.to_luma8();
+        assert!(image.width() > 0 && image.height() > 0, "a texture needs a non-empty image");
+        Self {image}
+    }
+
+    pub fn open<P: AsRef<Path>>(path: P) -> ImageResult<Self> {
+        Ok(Self::new(&image::open(path)?))
+    }
+
+    pub fn dimensions(&self) -> (u32, u32) {
+        self.image.dimensions()
+    }
+
+    pub fn texel(&self, x: i64, y: i64) -> Real {
+        let (width, height) = self.image.dimensions();
+        let x = x.rem_euclid(width as i64) as u32;
+        let y = y.rem_euclid(height as i64) as u32;
+        self.image.get_pixel(x, y).0[0] as Real / 255.0
+    }
+
+    // uv is in texture space, one unit per tile
+    pub fn lookup(&self, uv: &Vec2) -> Real {
+        self.bilinear(uv).0
+    }
+
+    // How fast lookup changes with uv
+    pub fn lookup_gradient(&self, uv: &Vec2) -> Vec2 {
+        self.bilinear(uv).1
+    }
+
+    fn bilinear(&self, uv: &Vec2) -> (Real, Vec2) {
+        let (width, height) = self.image.dimensions();
+        let p = vec2!(uv.x * width as Real - 0.5, uv.y * height as Real - 0.5);
+        let (x, y) = (p.x.floor(), p.y.floor());
+        let (tx, ty) = (p.x - x, p.y - y);
+        let (x, y) = (x as i64, y as i64);
+        let (a, b) = (self.texel(x, y), self.texel(x + 1, y));
+        let (c, d) = (self.texel(x, y + 1), self.texel(x + 1, y + 1));
+        let top = a * (1.0 - tx) + b * tx;
+        let bottom = c * (1.0 - tx) + d * tx;
+        let dx = (b - a) * (1.0 - ty) + (d - c) * ty;
+        let dy = bottom - top;
+        (top * (1.0 - ty) + bottom * ty, vec2!(dx * width as Real, dy * height as Real))
+    }
+}
+
+// A texture projected along an axis and tiled across the plane,
+// size being the world space width of one tile.
+// Black maps to 0 and white to amplitude
+pub struct ImageField {
+    texture: Texture,
+    axis: Axis,
+    size: Real,
+    amplitude: Real
+}
+
+impl ImageField {
+    pub fn new(image: &DynamicImage, axis: Axis, size: Real, amplitude: Real) -> Self {
+        Self {texture: Texture::new(image), axis, size, amplitude}
+    }
+
+    pub fn open<P: AsRef<Path>>(path: P, axis: Axis, size: Real, amplitude: Real) -> ImageResult<Self> {
+        Ok(Self::new(&image::open(path)?, axis, size, amplitude))
+    }
+}
+
+impl ScalarField for ImageField {
+    fn sample(&self, z: &Vec3) -> Real {
+        let (_, across) = self.axis.split(z);
+        self.amplitude * self.texture.lookup(&(across / self.size))
+    }
+
+    fn gradient(&self, z: &Vec3) -> Vec3 {
+        let (_, across) = self.axis.split(z);
+        let slope = self.texture.lookup_gradient(&(across / self.size)) * (self.amplitude / self.size);
+        self.axis.join(0.0, &slope)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::Luma;
+    use crate::objects::base::tests::check_gradient;
+    use crate::objects::Sphere;
+
+    #[test]
+    fn closure_field() {
+        let bumpy = Displace::new(Sphere::new(1.0), |z: &Vec3| 0.1 * z.x, 0.5);
+        assert_eq!(bumpy.distance_estimator(&vec3!(2, 0, 0)), 0.6);
+    }
+
+    #[test]
+    fn image_field_is_bilinear_and_tiles() {
+        let image = GrayImage::from_fn(2, 1, |x, _| Luma([if x == 0 { 0 } else { 255 }]));
+        let field = ImageField::new(&DynamicImage::ImageLuma8(image), Axis::Z, 2.0, 1.0);
+        // texel centers
+        assert_eq!(field.sample(&vec3!(0.5, 0, 0)), 0.0);
+        assert_eq!(field.sample(&vec3!(1.5, 0, 0)), 1.0);
+        // halfway between them, and across the seam
+        assert_eq!(field.sample(&vec3!(1.0, 0, 0)), 0.5);
+        assert_eq!(field.sample(&vec3!(2.0, 0, 0)), 0.5);
+        assert_eq!(field.sample(&vec3!(-0.5, 7, 3)), 1.0);
+    }
+
+    #[test]
+    fn exact_gradients() {
+        let image = GrayImage::from_fn(3, 2, |x, y| Luma([(x * 90 + y * 40) as u8]));
+        let field = ImageField::new(&DynamicImage::ImageLuma8(image), Axis::Y, 1.5, 0.2);
+        let p = vec3!(0.9, 0.3, 0.2);
+        check_gradient(&Displace::new(Sphere::new(1.0), field, 0.5), &p);
+        // closures go through the default, which differences the field only
+        check_gradient(&Displace::new(Sphere::new(1.0), |z: &Vec3| 0.1 * z.x * z.y, 0.8), &p);
+    }
+
+    #[test]
+    #[should_panic(expected = "non-empty image")]
+    fn texture_rejects_an_empty_image() {
+        Texture::new(&DynamicImage::new_luma8(0, 0));
+    }
+}
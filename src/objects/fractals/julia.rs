@@ -0,0 +1,122 @@
+use crate::linalg::{Real, Scalar, Number, Dual3, Vec3, Vec4};
+use crate::objects::base::{Object, Sample};
+use crate::objects::fractals::trap::{OrbitTrap, Orbit};
+
+// Quaternion arithmetic on Vec4 with the real part in x
+fn qsquare<S: Scalar>(q: &Vec4<S>) -> Vec4<S> {
+    let x2 = q.x + q.x;
+    Vec4::new(
+        q.x * q.x - q.y * q.y - q.z * q.z - q.w * q.w,
+        x2 * q.y,
+        x2 * q.z,
+        x2 * q.w
+    )
+}
+
+const BAILOUT: Real = 4.0;
+
+// The set of quaternions that stay bounded under q -> q^2 + c.
+// It lives in 4D, so we render the 3D slice where the last component
+// equals slice. Sweeping the slice animates through the whole set
+pub struct QuaternionJulia {
+    c: Vec4,
+    slice: Real,
+    iterations: u32,
+    trap: Option<OrbitTrap>
+}
+
+impl QuaternionJulia {
+    pub fn new(c: &Vec4, slice: Real, iterations: u32) -> Self {
+        Self {c: *c, slice, iterations, trap: None}
+    }
+
+    // The trap sees the orbit projected onto the first three components
+    pub fn with_trap(mut self, trap: OrbitTrap) -> Self {
+        self.trap = Some(trap);
+        self
+    }
+
+    pub fn set_slice(&mut self, slice: Real) {
+        self.slice = slice;
+    }
+}
+
+impl QuaternionJulia {
+    // Tracks |q'|^2 alongside q using |q'| -> 2 |q| |q'|, then uses the
+    // usual 0.5 * |q| * ln|q| / |q'|, written in squared magnitudes
+    fn iterate<S: Number>(&self, z: &Vec3<S>, mut visit: impl FnMut(&Vec3<S>)) -> S {
+        let zero = S::from_real(0.0);
+        let c = self.c.lift();
+        let mut q = Vec4::new(z.x, z.y, z.z, S::from_real(self.slice));
+        let mut m2 = q.dot(&q);
+        let mut dq2 = S::from_real(1.0);
+        for _ in 0..self.iterations {
+            dq2 *= S::from_real(4.0) * m2;
+            q = qsquare(&q) + c;
+            m2 = q.dot(&q);
+            visit(&q.xyz());
+            if m2 > S::from_real(BAILOUT * BAILOUT) {
+                break;
+            }
+        }
+        if m2 == zero {
+            zero
+        } else {
+            S::from_real(0.25) * m2.ln() * (m2 / dq2).sqrt()
+        }
+    }
+}
+
+impl Object for QuaternionJulia {
+    fn distance_estimator(&self, z: &Vec3) -> Real {
+        self.sample(z).distance
+    }
+
+    fn sample(&self, z: &Vec3) -> Sample {
+        let mut orbit = Orbit::new(self.trap.as_ref());
+        let distance = self.iterate(z, |q| orbit.visit(q));
+        Sample {distance, trap: orbit.value()}
+    }
+
+    fn distance_dual(&self, z: &Vec3<Dual3>) -> Dual3 {
+        self.iterate(z, |_| ())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::objects::base::tests::check_gradient;
+
+    #[test]
+    fn square() {
+        // (1 + i)^2 = 2i
+        assert_eq!(qsquare::<Real>(&vec4!(1, 1, 0, 0)), vec4!(0, 2, 0, 0));
+        // any unit imaginary squares to -1
+        let q: Vec4 = qsquare(&vec4!(0, 0.6, 0, 0.8));
+        assert!((q - vec4!(-1, 0, 0, 0)).len() < 1e-12);
+    }
+
+    #[test]
+    fn zero_constant_is_the_unit_ball() {
+        let julia = QuaternionJulia::new(&vec4!(0), 0.0, 20);
+        let d = julia.distance_estimator(&vec3!(0, 1.5, 0));
+        assert!(d > 0.0 && d <= 0.5);
+        assert!(julia.distance_estimator(&vec3!(0.5, 0, 0)) <= 0.0);
+    }
+
+    #[test]
+    fn slicing_moves_through_the_set() {
+        let mut julia = QuaternionJulia::new(&vec4!(0), 0.0, 20);
+        let p = vec3!(0.8, 0, 0);
+        assert!(julia.distance_estimator(&p) <= 0.0);
+        julia.set_slice(0.8);
+        assert!(julia.distance_estimator(&p) > 0.0);
+    }
+
+    #[test]
+    fn exact_gradients() {
+        let julia = QuaternionJulia::new(&vec4!(-0.2, 0.6, 0.2, 0), 0.1, 12);
+        check_gradient(&julia, &vec3!(0.9, 0.4, -0.6));
+    }
+}
@@ -0,0 +1,199 @@
+use crate::linalg::{Real, Scalar, Dual3, Vec3, Vec4};
+use crate::objects::base::{Object, Sample};
+use crate::objects::fractals::trap::{OrbitTrap, Orbit};
+
+// The running derivative rides along in w: every fold and scale is applied
+// to the whole Vec4, so w picks up exactly the factors the distance needs
+pub struct Mandelbox {
+    scale: Real,
+    min_radius: Real,
+    fixed_radius: Real,
+    iterations: u32,
+    trap: Option<OrbitTrap>
+}
+
+impl Mandelbox {
+    pub fn new(scale: Real, min_radius: Real, fixed_radius: Real, iterations: u32) -> Self {
+        Self {scale, min_radius, fixed_radius, iterations, trap: None}
+    }
+
+    pub fn with_trap(mut self, trap: OrbitTrap) -> Self {
+        self.trap = Some(trap);
+        self
+    }
+}
+
+impl Default for Mandelbox {
+    fn default() -> Self {
+        Self::new(2.0, 0.5, 1.0, 12)
+    }
+}
+
+impl Mandelbox {
+    fn iterate<S: Scalar>(&self, z: &Vec3<S>, mut visit: impl FnMut(&Vec3<S>)) -> S {
+        let one = S::from_real(1.0);
+        let c = Vec4::new(z.x, z.y, z.z, one);
+        let mut p = c;
+        let min_r2 = S::from_real(self.min_radius * self.min_radius);
+        let fixed_r2 = S::from_real(self.fixed_radius * self.fixed_radius);
+        for _ in 0..self.iterations {
+            // box fold, reflecting everything outside [-1, 1] back in
+            let xyz = p.xyz();
+            let folded = xyz.clamp(-one, one) * S::from_real(2.0) - xyz;
+            p = Vec4::new(folded.x, folded.y, folded.z, p.w);
+            // sphere fold, inverting through the fixed sphere
+            let r2 = folded.dot(&folded);
+            p *= r2.select_lt(min_r2, fixed_r2 / min_r2, r2.select_lt(fixed_r2, fixed_r2 / r2, one));
+            p = p * S::from_real(self.scale) + c;
+            visit(&p.xyz());
+        }
+        let c1 = S::from_real((self.scale - 1.0).abs());
+        let c2 = S::from_real(self.scale.abs().powi(1 - self.iterations as i32));
+        (p.xyz().len() - c1) / p.w.abs() - c2
+    }
+}
+
+impl Object for Mandelbox {
+    fn distance_estimator(&self, z: &Vec3) -> Real {
+        self.sample(z).distance
+    }
+
+    fn sample(&self, z: &Vec3) -> Sample {
+        let mut orbit = Orbit::new(self.trap.as_ref());
+        let distance = self.iterate(z, |p| orbit.visit(p));
+        Sample {distance, trap: orbit.value()}
+    }
+
+    fn distance_dual(&self, z: &Vec3<Dual3>) -> Dual3 {
+        self.iterate(z, |_| ())
+    }
+}
+
+// Kaleidoscopic IFS: fold space through a set of planes through the origin,
+// rotate, then scale away from the offset point, over and over.
+// The result is a cloud of ever smaller copies of the offset point
+pub struct Kifs {
+    planes: Vec<Vec3>,
+    rotations: Vec<(Vec3, Real, Real)>,
+    scale: Real,
+    offset: Vec3,
+    iterations: u32,
+    trap: Option<OrbitTrap>
+}
+
+impl Kifs {
+    // The planes are given by their normals and fold towards the side
+    // the normal points to
+    pub fn new(planes: Vec<Vec3>, scale: Real, offset: &Vec3, iterations: u32) -> Self {
+        let planes = planes.iter().map(|n| n.normalized()).collect();
+        Self {planes, rotations: vec![], scale, offset: *offset, iterations, trap: None}
+    }
+
+    // Adds a rotation applied after the folds each iteration
+    pub fn rotated(mut self, axis: &Vec3, angle: Real) -> Self {
+        let (sin, cos) = angle.sin_cos();
+        self.rotations.push((axis.normalized(), sin, cos));
+        self
+    }
+
+    pub fn with_trap(mut self, trap: OrbitTrap) -> Self {
+        self.trap = Some(trap);
+        self
+    }
+}
+
+// Rodrigues' formula, the axis being of unit length
+fn rotate<S: Scalar>(p: &Vec3<S>, axis: &Vec3, sin: Real, cos: Real) -> Vec3<S> {
+    let axis = axis.lift();
+    *p * S::from_real(cos) + axis.cross(p) * S::from_real(sin) + axis * axis.dot(p) * S::from_real(1.0 - cos)
+}
+
+impl Kifs {
+    fn iterate<S: Scalar>(&self, z: &Vec3<S>, mut visit: impl FnMut(&Vec3<S>)) -> S {
+        let zero = S::from_real(0.0);
+        let scale = S::from_real(self.scale);
+        let shift = self.offset.lift::<S>() * S::from_real(self.scale - 1.0);
+        let mut xyz = *z;
+        let mut w = S::from_real(1.0);
+        for _ in 0..self.iterations {
+            for n in self.planes.iter() {
+                let n = n.lift();
+                xyz -= n * S::from_real(2.0) * xyz.dot(&n).min(zero);
+            }
+            for (axis, sin, cos) in self.rotations.iter() {
+                xyz = rotate(&xyz, axis, *sin, *cos);
+            }
+            xyz = xyz * scale - shift;
+            w *= scale;
+            visit(&xyz);
+        }
+        xyz.len() / w.abs()
+    }
+}
+
+impl Object for Kifs {
+    fn distance_estimator(&self, z: &Vec3) -> Real {
+        self.sample(z).distance
+    }
+
+    fn sample(&self, z: &Vec3) -> Sample {
+        let mut orbit = Orbit::new(self.trap.as_ref());
+        let distance = self.iterate(z, |p| orbit.visit(p));
+        Sample {distance, trap: orbit.value()}
+    }
+
+    fn distance_dual(&self, z: &Vec3<Dual3>) -> Dual3 {
+        self.iterate(z, |_| ())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::linalg::approx::EPSILON;
+    use crate::objects::base::tests::check_gradient;
+
+    #[test]
+    fn mandelbox_far_away() {
+        let mandelbox = Mandelbox::default();
+        // the scale 2 box fits within |x| <= 4 or so
+        let d = mandelbox.distance_estimator(&vec3!(10, 0, 0));
+        assert!(d > 0.0 && d <= 6.0);
+    }
+
+    #[test]
+    fn mandelbox_origin_inside() {
+        assert!(Mandelbox::default().distance_estimator(&vec3!(0)) <= 0.0);
+    }
+
+    #[test]
+    fn kifs_without_iterations_is_a_point() {
+        let kifs = Kifs::new(vec![vec3!(1, 0, 0)], 2.0, &vec3!(1), 0);
+        assert_eq!(kifs.distance_estimator(&vec3!(0, 3, 4)), 5.0);
+    }
+
+    #[test]
+    fn kifs_fold_is_symmetric() {
+        let kifs = Kifs::new(vec![vec3!(1, 0, 0)], 2.0, &vec3!(1, 0, 0), 4)
+            .rotated(&vec3!(0, 0, 1), 0.3);
+        assert_eq!(
+            kifs.distance_estimator(&vec3!(0.7, 0.2, 0.1)),
+            kifs.distance_estimator(&vec3!(-0.7, 0.2, 0.1))
+        );
+    }
+
+    #[test]
+    fn exact_gradients() {
+        check_gradient(&Mandelbox::new(2.0, 0.5, 1.0, 4), &vec3!(3.1, 2.2, -1.7));
+        let kifs = Kifs::new(vec![vec3!(1, 0, 0), vec3!(0, 1, 0), vec3!(1, -1, 0)], 2.0, &vec3!(1), 3)
+            .rotated(&vec3!(0, 0, 1), 0.3);
+        check_gradient(&kifs, &vec3!(0.4, -0.8, 1.3));
+    }
+
+    #[test]
+    fn rodrigues() {
+        let (sin, cos) = crate::linalg::consts::FRAC_PI_2.sin_cos();
+        let p: Vec3 = rotate(&vec3!(1, 0, 0), &vec3!(0, 0, 1), sin, cos);
+        assert!((p - vec3!(0, 1, 0)).len() < EPSILON);
+    }
+}
@@ -0,0 +1,109 @@
+use crate::linalg::{Real, Number, Dual3, Vec3};
+use crate::objects::base::{Object, Sample};
+use crate::objects::fractals::trap::{OrbitTrap, Orbit};
+
+pub struct Mandelbulb {
+    power: Real,
+    iterations: u32,
+    bailout: Real,
+    trap: Option<OrbitTrap>
+}
+
+impl Mandelbulb {
+    pub fn new(power: Real, iterations: u32, bailout: Real) -> Self {
+        Self {power, iterations, bailout, trap: None}
+    }
+
+    pub fn with_trap(mut self, trap: OrbitTrap) -> Self {
+        self.trap = Some(trap);
+        self
+    }
+}
+
+impl Default for Mandelbulb {
+    // The classic power 8 bulb
+    fn default() -> Self {
+        Self::new(8.0, 16, 2.0)
+    }
+}
+
+impl Mandelbulb {
+    // Iterates z -> z^power + c in spherical coordinates, carrying the running
+    // derivative dr along to get the distance estimate 0.5 * ln(r) * r / dr
+    fn iterate<S: Number>(&self, c: &Vec3<S>, mut visit: impl FnMut(&Vec3<S>)) -> S {
+        let (zero, one) = (S::from_real(0.0), S::from_real(1.0));
+        let power = S::from_real(self.power);
+        let bailout = S::from_real(self.bailout);
+        let mut z = *c;
+        let mut dr = one;
+        let mut r = z.len();
+        for _ in 0..self.iterations {
+            if r > bailout || r == zero {
+                break;
+            }
+            let theta = (z.z / r).acos() * power;
+            let phi = z.y.atan2(z.x) * power;
+            dr = r.powf(power - one) * power * dr + one;
+            z = Vec3::new(
+                theta.sin() * phi.cos(),
+                theta.sin() * phi.sin(),
+                theta.cos()
+            ) * r.powf(power) + *c;
+            r = z.len();
+            visit(&z);
+        }
+        if r == zero {
+            // the origin is a fixed point, and firmly inside
+            zero
+        } else {
+            S::from_real(0.5) * r.ln() * r / dr
+        }
+    }
+}
+
+impl Object for Mandelbulb {
+    fn distance_estimator(&self, z: &Vec3) -> Real {
+        self.sample(z).distance
+    }
+
+    fn sample(&self, c: &Vec3) -> Sample {
+        let mut orbit = Orbit::new(self.trap.as_ref());
+        let distance = self.iterate(c, |z| orbit.visit(z));
+        Sample {distance, trap: orbit.value()}
+    }
+
+    fn distance_dual(&self, c: &Vec3<Dual3>) -> Dual3 {
+        self.iterate(c, |_| ())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::objects::base::tests::check_gradient;
+
+    #[test]
+    fn outside_is_positive_and_conservative() {
+        let bulb = Mandelbulb::default();
+        for p in [vec3!(0, 0, 3), vec3!(2, -2, 1), vec3!(0, 1.5, 0)].iter() {
+            let d = bulb.distance_estimator(p);
+            assert!(d > 0.0);
+            // the bulb contains a small ball around the origin
+            assert!(d < p.len() - 0.5);
+        }
+    }
+
+    #[test]
+    fn inside_is_not_positive() {
+        let bulb = Mandelbulb::default();
+        assert!(bulb.distance_estimator(&vec3!(0.2, 0.1, 0)) <= 0.0);
+        assert_eq!(bulb.distance_estimator(&vec3!(0)), 0.0);
+    }
+
+    #[test]
+    fn exact_gradients() {
+        let bulb = Mandelbulb::default();
+        check_gradient(&bulb, &vec3!(0.3, 0.9, 0.7));
+        check_gradient(&bulb, &vec3!(1.5, -0.2, 0.4));
+    }
+}
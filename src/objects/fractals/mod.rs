@@ -0,0 +1,11 @@
+pub mod mandelbulb;
+pub mod mandelbox;
+pub mod sponge;
+pub mod julia;
+pub mod trap;
+
+pub use mandelbulb::Mandelbulb;
+pub use mandelbox::{Mandelbox, Kifs};
+pub use sponge::{MengerSponge, SierpinskiTetrahedron};
+pub use julia::QuaternionJulia;
+pub use trap::OrbitTrap;
@@ -0,0 +1,181 @@
+use crate::linalg::{Real, Scalar, Dual3, Vec3};
+use crate::objects::base::{Object, Sample};
+use crate::objects::fractals::trap::{OrbitTrap, Orbit};
+use crate::objects::primitives::Cube;
+
+// A cube of half-width size with the infinite cross cut out at every level.
+// The cross is repeated with modulo so one evaluation covers all holes
+// of a level, which keeps this exact and cheap at any depth
+pub struct MengerSponge {
+    size: Real,
+    iterations: u32,
+    trap: Option<OrbitTrap>
+}
+
+impl MengerSponge {
+    pub fn new(size: Real, iterations: u32) -> Self {
+        Self {size, iterations, trap: None}
+    }
+
+    pub fn with_trap(mut self, trap: OrbitTrap) -> Self {
+        self.trap = Some(trap);
+        self
+    }
+}
+
+impl MengerSponge {
+    // The orbit is the point within each level's repeated cell
+    fn iterate<S: Scalar>(&self, z: &Vec3<S>, mut visit: impl FnMut(&Vec3<S>)) -> S {
+        let one = S::from_real(1.0);
+        let p = *z / S::from_real(self.size);
+        let mut d = Cube::new(&vec3!(1)).distance(&p);
+        let mut s = 1.0;
+        for _ in 0..self.iterations {
+            let a = (p * S::from_real(s)).modulo(S::from_real(2.0)) - one;
+            visit(&a);
+            s *= 3.0;
+            let r = (a.abs() * S::from_real(-3.0) + one).abs();
+            // distance to the cross made of three infinite square bars
+            let cross = r.x.max(r.y).min(r.y.max(r.z)).min(r.z.max(r.x));
+            d = d.max((cross - one) / S::from_real(s));
+        }
+        d * S::from_real(self.size)
+    }
+}
+
+impl Object for MengerSponge {
+    fn distance_estimator(&self, z: &Vec3) -> Real {
+        self.sample(z).distance
+    }
+
+    fn sample(&self, z: &Vec3) -> Sample {
+        let mut orbit = Orbit::new(self.trap.as_ref());
+        let distance = self.iterate(z, |a| orbit.visit(a));
+        Sample {distance, trap: orbit.value()}
+    }
+
+    fn distance_dual(&self, z: &Vec3<Dual3>) -> Dual3 {
+        self.iterate(z, |_| ())
+    }
+}
+
+// Folds space into one corner of the tetrahedron and scales it up,
+// ending in the distance to a solid tetrahedron
+pub struct SierpinskiTetrahedron {
+    scale: Real,
+    iterations: u32,
+    trap: Option<OrbitTrap>
+}
+
+impl SierpinskiTetrahedron {
+    pub fn new(scale: Real, iterations: u32) -> Self {
+        Self {scale, iterations, trap: None}
+    }
+
+    pub fn with_trap(mut self, trap: OrbitTrap) -> Self {
+        self.trap = Some(trap);
+        self
+    }
+}
+
+impl Default for SierpinskiTetrahedron {
+    fn default() -> Self {
+        Self::new(2.0, 10)
+    }
+}
+
+// Regular tetrahedron with vertices (1, 1, 1), (-1, -1, 1), (1, -1, -1) and (-1, 1, -1)
+fn tetrahedron<S: Scalar>(p: &Vec3<S>) -> S {
+    ((p.x + p.y).abs() - p.z).max((p.x - p.y).abs() + p.z) - S::from_real(1.0)
+}
+
+impl SierpinskiTetrahedron {
+    fn iterate<S: Scalar>(&self, z: &Vec3<S>, mut visit: impl FnMut(&Vec3<S>)) -> S {
+        let zero = S::from_real(0.0);
+        let mut p = *z;
+        for _ in 0..self.iterations {
+            // fold across each plane the point is on the wrong side of
+            let s = p.x + p.y;
+            p = Vec3::new(s.select_lt(zero, -p.y, p.x), s.select_lt(zero, -p.x, p.y), p.z);
+            let s = p.x + p.z;
+            p = Vec3::new(s.select_lt(zero, -p.z, p.x), p.y, s.select_lt(zero, -p.x, p.z));
+            let s = p.y + p.z;
+            p = Vec3::new(p.x, s.select_lt(zero, -p.z, p.y), s.select_lt(zero, -p.y, p.z));
+            p = p * S::from_real(self.scale) - S::from_real(self.scale - 1.0);
+            visit(&p);
+        }
+        tetrahedron(&p) / S::from_real(Real::sqrt(3.0)) * S::from_real(self.scale.powi(-(self.iterations as i32)))
+    }
+}
+
+impl Object for SierpinskiTetrahedron {
+    fn distance_estimator(&self, z: &Vec3) -> Real {
+        self.sample(z).distance
+    }
+
+    fn sample(&self, z: &Vec3) -> Sample {
+        let mut orbit = Orbit::new(self.trap.as_ref());
+        let distance = self.iterate(z, |p| orbit.visit(p));
+        Sample {distance, trap: orbit.value()}
+    }
+
+    fn distance_dual(&self, z: &Vec3<Dual3>) -> Dual3 {
+        self.iterate(z, |_| ())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::objects::base::tests::check_gradient;
+
+    #[test]
+    fn menger_without_iterations_is_a_cube() {
+        let sponge = MengerSponge::new(2.0, 0);
+        let cube = Cube::new(&vec3!(2));
+        for p in [vec3!(3, 0, 0), vec3!(1, 1, 1), vec3!(0, -5, 4)].iter() {
+            assert_eq!(sponge.distance_estimator(p), cube.distance_estimator(p));
+        }
+    }
+
+    #[test]
+    fn menger_center_is_hollow() {
+        let sponge = MengerSponge::new(1.0, 1);
+        assert!(sponge.distance_estimator(&vec3!(0)) > 0.0);
+        // but the corners are solid
+        assert!(sponge.distance_estimator(&vec3!(0.9, 0.9, 0.9)) < 0.0);
+    }
+
+    #[test]
+    fn menger_trap() {
+        let plain = MengerSponge::new(1.0, 2);
+        let trapped = MengerSponge::new(1.0, 2).with_trap(OrbitTrap::Point(vec3!(0)));
+        let p = vec3!(0.1, 0.2, 0.7);
+        assert_eq!(plain.sample(&p).trap, None);
+        assert_eq!(trapped.sample(&p).distance, plain.distance_estimator(&p));
+        // at the center every level's cell is visited at its corner
+        assert_eq!(trapped.sample(&vec3!(0)).trap, Some(Real::sqrt(3.0)));
+    }
+
+    #[test]
+    fn exact_gradients() {
+        check_gradient(&MengerSponge::new(1.0, 3), &vec3!(0.52, 1.3, 0.81));
+        check_gradient(&SierpinskiTetrahedron::default(), &vec3!(0.3, -0.7, 1.4));
+    }
+
+    #[test]
+    fn sierpinski_corners_are_on_the_surface() {
+        for iterations in 0..4 {
+            let tetra = SierpinskiTetrahedron::new(2.0, iterations);
+            for v in [vec3!(1, 1, 1), vec3!(-1, -1, 1), vec3!(1, -1, -1), vec3!(-1, 1, -1)].iter() {
+                assert!(tetra.distance_estimator(v).abs() < 1e-12);
+            }
+        }
+    }
+
+    #[test]
+    fn sierpinski_center_is_hollow() {
+        assert!(SierpinskiTetrahedron::new(2.0, 0).distance_estimator(&vec3!(0)) < 0.0);
+        assert!(SierpinskiTetrahedron::new(2.0, 1).distance_estimator(&vec3!(0)) >= 0.0);
+    }
+}
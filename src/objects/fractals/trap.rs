@@ -0,0 +1,82 @@
+use crate::linalg::{Real, Vec3};
+
+// Shapes the orbit of a fractal iteration is measured against. The closest
+// the orbit gets ends up in Sample::trap, which makes for nice coloring.
+// Normals and directions are expected to be of unit length
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum OrbitTrap {
+    Point(Vec3),
+    // normal and distance from the origin
+    Plane(Vec3, Real),
+    // a line through the origin
+    Axis(Vec3)
+}
+
+impl OrbitTrap {
+    pub fn distance(&self, z: &Vec3) -> Real {
+        match self {
+            OrbitTrap::Point(p) => (*z - *p).len(),
+            OrbitTrap::Plane(n, d) => (z.dot(n) - d).abs(),
+            OrbitTrap::Axis(dir) => (*z - *dir * z.dot(dir)).len()
+        }
+    }
+}
+
+// Running minimum over an orbit, doing nothing when no trap is set
+pub(crate) struct Orbit<'a> {
+    trap: Option<&'a OrbitTrap>,
+    min: Real
+}
+
+impl<'a> Orbit<'a> {
+    pub fn new(trap: Option<&'a OrbitTrap>) -> Self {
+        Self {trap, min: 1.0 / 0.0}
+    }
+
+    pub fn visit(&mut self, z: &Vec3) {
+        if let Some(trap) = self.trap {
+            self.min = self.min.min(trap.distance(z));
+        }
+    }
+
+    pub fn value(&self) -> Option<Real> {
+        self.trap.map(|_| self.min)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::objects::{Scene, Object, Union, Sphere, Mandelbulb};
+
+    #[test]
+    fn distances() {
+        let z = vec3!(3, 4, 0);
+        assert_eq!(OrbitTrap::Point(vec3!(0)).distance(&z), 5.0);
+        assert_eq!(OrbitTrap::Plane(vec3!(0, 1, 0), 1.0).distance(&z), 3.0);
+        assert_eq!(OrbitTrap::Axis(vec3!(1, 0, 0)).distance(&z), 4.0);
+    }
+
+    #[test]
+    fn orbit_keeps_the_minimum() {
+        let trap = OrbitTrap::Point(vec3!(0));
+        let mut orbit = Orbit::new(Some(&trap));
+        orbit.visit(&vec3!(2, 0, 0));
+        orbit.visit(&vec3!(0, 1, 0));
+        orbit.visit(&vec3!(0, 0, 3));
+        assert_eq!(orbit.value(), Some(1.0));
+        assert_eq!(Orbit::new(None).value(), None);
+    }
+
+    #[test]
+    fn trap_reaches_the_scene() {
+        let mut scene = Scene::new();
+        let bulb = Mandelbulb::default().with_trap(OrbitTrap::Point(vec3!(0)));
+        scene.add(Union(bulb, Sphere::new(0.1)));
+        let near_bulb = vec3!(0, 0, 1.5);
+        assert!(scene.sample(&near_bulb).trap.is_some());
+        assert_eq!(scene.sample(&near_bulb).distance, scene.distance_estimator(&near_bulb));
+        // plain objects have nothing to report
+        assert_eq!(Sphere::new(0.1).sample(&vec3!(0, 0.2, 0)).trap, None);
+    }
+}
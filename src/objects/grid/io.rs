@@ -0,0 +1,320 @@
+use std::convert::{TryFrom, TryInto};
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::path::Path;
+use crate::linalg::{Real, Number, Vec3};
+use crate::objects::grid::SdfGrid;
+
+// Two formats, both little endian:
+//
+// Our own, with a 72 byte header
+//   magic "SDFG", version u32
+//   min and max corners, 6 x f64
+//   resolution, 3 x u32
+//   data type u32, 0 for f32 and 1 for f64
+// followed by the node values, x first, then y, then z.
+//
+// Mitsuba's gridvolume .vol, which other tools can read and write
+//   "VOL", version byte 3
+//   encoding i32, 1 for f32
+//   resolution, 3 x i32, and channel count i32, which must be 1
+//   min and max corners, 6 x f32
+// followed by f32 node values in the same order
+
+const MAGIC: &[u8; 4] = b"SDFG";
+const VERSION: u32 = 1;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DataType {
+    F32,
+    F64
+}
+
+fn invalid(msg: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg.to_string())
+}
+
+fn read_array<R: Read, const N: usize>(r: &mut R) -> io::Result<[u8; N]> {
+    let mut buf = [0u8; N];
+    r.read_exact(&mut buf)?;
+    Ok(buf)
+}
+
+fn read_u32<R: Read>(r: &mut R) -> io::Result<u32> {
+    Ok(u32::from_le_bytes(read_array(r)?))
+}
+
+fn read_f32<R: Read>(r: &mut R) -> io::Result<f32> {
+    Ok(f32::from_le_bytes(read_array(r)?))
+}
+
+fn read_f64<R: Read>(r: &mut R) -> io::Result<f64> {
+    Ok(f64::from_le_bytes(read_array(r)?))
+}
+
+fn read_resolution<R: Read>(r: &mut R) -> io::Result<[usize; 3]> {
+    let mut resolution = [0; 3];
+    for n in resolution.iter_mut() {
+        *n = read_u32(r)? as usize;
+    }
+    if resolution.iter().any(|&n| n < 2) {
+        return Err(invalid("a grid needs at least two nodes along each axis"));
+    }
+    Ok(resolution)
+}
+
+// Corners come min first, and the grid needs some room along every axis
+fn bounds(corners: [Real; 6]) -> io::Result<(Vec3, Vec3)> {
+    let min = vec3!(corners[0], corners[1], corners[2]);
+    let max = vec3!(corners[3], corners[4], corners[5]);
+    // written so that NaN fails too
+    if !(min.x < max.x && min.y < max.y && min.z < max.z) {
+        return Err(invalid("grid bounds are inverted or not numbers"));
+    }
+    Ok((min, max))
+}
+
+// The resolution as the integer type of a format, checked before anything
+// is written so a failure doesn't leave half a file behind
+fn resolution_as<T: TryFrom<usize>>(resolution: [usize; 3]) -> io::Result<[T; 3]> {
+    let convert = |n: usize| T::try_from(n)
+        .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "grid resolution is too large for the format"));
+    Ok([convert(resolution[0])?, convert(resolution[1])?, convert(resolution[2])?])
+}
+
+fn read_data<R: Read>(r: &mut R, resolution: [usize; 3], data_type: DataType) -> io::Result<Vec<Real>> {
+    let size = match data_type {
+        DataType::F32 => 4,
+        DataType::F64 => 8
+    };
+    let len = resolution.iter().try_fold(size, |len: usize, &n| len.checked_mul(n))
+        .ok_or_else(|| invalid("grid resolution is too large"))?;
+    // read what's actually there rather than trusting the header with an
+    // allocation up front
+    let mut bytes = vec![];
+    r.take(len as u64).read_to_end(&mut bytes)?;
+    if bytes.len() != len {
+        return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "grid data is truncated"));
+    }
+    Ok(bytes.chunks(size).map(|b| match data_type {
+        DataType::F32 => f32::from_le_bytes(b.try_into().unwrap()) as Real,
+        DataType::F64 => f64::from_le_bytes(b.try_into().unwrap()) as Real
+    }).collect())
+}
+
+impl SdfGrid {
+    pub fn write<W: Write>(&self, w: &mut W, data_type: DataType) -> io::Result<()> {
+        let resolution: [u32; 3] = resolution_as(self.resolution)?;
+        w.write_all(MAGIC)?;
+        w.write_all(&VERSION.to_le_bytes())?;
+        for c in [self.min.x, self.min.y, self.min.z, self.max.x, self.max.y, self.max.z].iter() {
+            w.write_all(&c.to_f64().to_le_bytes())?;
+        }
+        for n in resolution.iter() {
+            w.write_all(&n.to_le_bytes())?;
+        }
+        let tag: u32 = match data_type {
+            DataType::F32 => 0,
+            DataType::F64 => 1
+        };
+        w.write_all(&tag.to_le_bytes())?;
+        for v in self.data.iter() {
+            match data_type {
+                DataType::F32 => w.write_all(&(v.to_f64() as f32).to_le_bytes())?,
+                DataType::F64 => w.write_all(&v.to_f64().to_le_bytes())?
+            }
+        }
+        Ok(())
+    }
+
+    pub fn read<R: Read>(r: &mut R) -> io::Result<Self> {
+        if &read_array::<_, 4>(r)? != MAGIC {
+            return Err(invalid("not a baked distance field"));
+        }
+        if read_u32(r)? != VERSION {
+            return Err(invalid("unsupported baked distance field version"));
+        }
+        let mut corners = [0.0; 6];
+        for c in corners.iter_mut() {
+            *c = read_f64(r)? as Real;
+        }
+        let resolution = read_resolution(r)?;
+        let data_type = match read_u32(r)? {
+            0 => DataType::F32,
+            1 => DataType::F64,
+            _ => return Err(invalid("unknown data type"))
+        };
+        let data = read_data(r, resolution, data_type)?;
+        let (min, max) = bounds(corners)?;
+        Ok(Self::new(&min, &max, resolution, data))
+    }
+
+    pub fn write_vol<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        let resolution: [i32; 3] = resolution_as(self.resolution)?;
+        w.write_all(b"VOL")?;
+        w.write_all(&[3])?;
+        w.write_all(&1i32.to_le_bytes())?;
+        for n in resolution.iter() {
+            w.write_all(&n.to_le_bytes())?;
+        }
+        w.write_all(&1i32.to_le_bytes())?;
+        for c in [self.min.x, self.min.y, self.min.z, self.max.x, self.max.y, self.max.z].iter() {
+            w.write_all(&(c.to_f64() as f32).to_le_bytes())?;
+        }
+        for v in self.data.iter() {
+            w.write_all(&(v.to_f64() as f32).to_le_bytes())?;
+        }
+        Ok(())
+    }
+
+    pub fn read_vol<R: Read>(r: &mut R) -> io::Result<Self> {
+        let header: [u8; 4] = read_array(r)?;
+        if &header[..3] != b"VOL" || header[3] != 3 {
+            return Err(invalid("not a version 3 .vol file"));
+        }
+        if read_u32(r)? != 1 {
+            return Err(invalid("only float32 .vol files are supported"));
+        }
+        let resolution = read_resolution(r)?;
+        if read_u32(r)? != 1 {
+            return Err(invalid("a distance field has a single channel"));
+        }
+        let mut corners = [0.0; 6];
+        for c in corners.iter_mut() {
+            *c = read_f32(r)? as Real;
+        }
+        let data = read_data(r, resolution, DataType::F32)?;
+        let (min, max) = bounds(corners)?;
+        Ok(Self::new(&min, &max, resolution, data))
+    }
+
+    // Writes .vol files as such, anything else in our own format.
+    // .vol only holds f32
+    pub fn save<P: AsRef<Path>>(&self, path: P, data_type: DataType) -> io::Result<()> {
+        let path = path.as_ref();
+        if is_vol(path) && data_type != DataType::F32 {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, ".vol files only hold f32 data"));
+        }
+        let mut w = BufWriter::new(File::create(path)?);
+        if is_vol(path) {
+            self.write_vol(&mut w)?;
+        } else {
+            self.write(&mut w, data_type)?;
+        }
+        w.flush()
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let path = path.as_ref();
+        let mut r = BufReader::new(File::open(path)?);
+        if is_vol(path) {
+            Self::read_vol(&mut r)
+        } else {
+            Self::read(&mut r)
+        }
+    }
+}
+
+fn is_vol(path: &Path) -> bool {
+    path.extension().and_then(|e| e.to_str()).map(|e| e.eq_ignore_ascii_case("vol")).unwrap_or(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::objects::{Object, Sphere};
+
+    fn grid() -> SdfGrid {
+        SdfGrid::bake(&Sphere::new(1.0), &vec3!(-1.5, -2, -1), &vec3!(1.5, 2, 1), [4, 5, 3])
+    }
+
+    #[test]
+    fn roundtrip_f64_is_lossless() {
+        let original = grid();
+        let mut bytes = vec![];
+        original.write(&mut bytes, DataType::F64).unwrap();
+        assert_eq!(bytes.len(), 72 + 4 * 5 * 3 * 8);
+        let loaded = SdfGrid::read(&mut bytes.as_slice()).unwrap();
+        assert_eq!(loaded.min(), original.min());
+        assert_eq!(loaded.max(), original.max());
+        assert_eq!(loaded.resolution(), original.resolution());
+        assert_eq!(loaded.data(), original.data());
+    }
+
+    #[test]
+    fn roundtrip_f32_and_vol() {
+        let original = grid();
+        let mut native = vec![];
+        original.write(&mut native, DataType::F32).unwrap();
+        let mut vol = vec![];
+        original.write_vol(&mut vol).unwrap();
+        for loaded in [SdfGrid::read(&mut native.as_slice()).unwrap(), SdfGrid::read_vol(&mut vol.as_slice()).unwrap()].iter() {
+            assert_eq!(loaded.resolution(), original.resolution());
+            let p = vec3!(0.3, -0.2, 0.1);
+            assert!((loaded.distance_estimator(&p) - original.distance_estimator(&p)).abs() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn rejects_garbage() {
+        assert!(SdfGrid::read(&mut &b"nope"[..]).is_err());
+        assert!(SdfGrid::read_vol(&mut &b"VOL\x02"[..]).is_err());
+        // truncated data
+        let mut bytes = vec![];
+        grid().write(&mut bytes, DataType::F32).unwrap();
+        assert!(SdfGrid::read(&mut &bytes[..bytes.len() - 1]).is_err());
+        // a resolution whose byte count overflows
+        let mut header = bytes[..72].to_vec();
+        for n in header[56..68].iter_mut() {
+            *n = 0xff;
+        }
+        let err = SdfGrid::read(&mut header.as_slice()).err().unwrap();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn rejects_bad_bounds() {
+        let mut bytes = vec![];
+        grid().write(&mut bytes, DataType::F32).unwrap();
+        // swap min.x and max.x, then make min.y NaN
+        let mut inverted = bytes.clone();
+        inverted[8..16].copy_from_slice(&1.5f64.to_le_bytes());
+        inverted[32..40].copy_from_slice(&(-1.5f64).to_le_bytes());
+        let mut nan = bytes.clone();
+        nan[16..24].copy_from_slice(&f64::NAN.to_le_bytes());
+        for bytes in [inverted, nan].iter() {
+            let err = SdfGrid::read(&mut bytes.as_slice()).err().unwrap();
+            assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        }
+    }
+
+    #[test]
+    fn too_fine_for_vol() {
+        // only the header matters, so skip the data that would come with it
+        let fine = SdfGrid {resolution: [1 << 31, 2, 2], data: vec![], ..grid()};
+        let mut bytes = vec![];
+        assert_eq!(fine.write_vol(&mut bytes).err().unwrap().kind(), io::ErrorKind::InvalidInput);
+        assert!(bytes.is_empty());
+        assert!(fine.write(&mut bytes, DataType::F32).is_ok());
+    }
+
+    #[test]
+    fn vol_only_holds_f32() {
+        let path = std::env::temp_dir().join("raymarcher_grid_f64_test.vol");
+        let err = grid().save(&path, DataType::F64).err().unwrap();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn save_and_load() {
+        let dir = std::env::temp_dir();
+        for name in ["raymarcher_grid_test.sdf", "raymarcher_grid_test.vol"].iter() {
+            let path = dir.join(name);
+            grid().save(&path, DataType::F32).unwrap();
+            let loaded = SdfGrid::load(&path).unwrap();
+            std::fs::remove_file(&path).unwrap();
+            assert_eq!(loaded.resolution(), grid().resolution());
+        }
+    }
+}
@@ -0,0 +1,226 @@
+pub mod io;
+
+pub use self::io::DataType;
+
+use crate::linalg::{Real, Scalar, Dual3, Vec3};
+use crate::objects::base::Object;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Interpolation {
+    Trilinear,
+    // Catmull-Rom through the 4x4x4 neighbourhood. Smoother, so normals look
+    // better, but it can overshoot a little near sharp features
+    Tricubic
+}
+
+// A distance field sampled at the nodes of a regular grid spanning min to max,
+// with the first node on min and the last on max. Data is laid out x first,
+// then y, then z
+pub struct SdfGrid {
+    min: Vec3,
+    max: Vec3,
+    resolution: [usize; 3],
+    data: Vec<Real>,
+    interpolation: Interpolation
+}
+
+impl SdfGrid {
+    pub fn new(min: &Vec3, max: &Vec3, resolution: [usize; 3], data: Vec<Real>) -> Self {
+        assert!(resolution.iter().all(|&n| n >= 2), "a grid needs at least two nodes along each axis");
+        assert!(min.x < max.x && min.y < max.y && min.z < max.z, "a grid needs max above min along each axis");
+        assert_eq!(data.len(), resolution[0] * resolution[1] * resolution[2], "grid data doesn't match its resolution");
+        Self {min: *min, max: *max, resolution, data, interpolation: Interpolation::Trilinear}
+    }
+
+    // Samples the object at every node. Handy for caching something expensive
+    // like a deep fractal, as long as the bounds cover it
+    pub fn bake<O: Object>(obj: &O, min: &Vec3, max: &Vec3, resolution: [usize; 3]) -> Self {
+        let mut grid = Self::new(min, max, resolution, vec![0.0; resolution[0] * resolution[1] * resolution[2]]);
+        for k in 0..resolution[2] {
+            for j in 0..resolution[1] {
+                for i in 0..resolution[0] {
+                    let p = grid.node(i, j, k);
+                    let index = grid.index(i, j, k);
+                    grid.data[index] = obj.distance_estimator(&p);
+                }
+            }
+        }
+        grid
+    }
+
+    pub fn with_interpolation(mut self, interpolation: Interpolation) -> Self {
+        self.interpolation = interpolation;
+        self
+    }
+
+    pub fn min(&self) -> Vec3 {
+        self.min
+    }
+
+    pub fn max(&self) -> Vec3 {
+        self.max
+    }
+
+    pub fn resolution(&self) -> [usize; 3] {
+        self.resolution
+    }
+
+    pub fn data(&self) -> &[Real] {
+        &self.data
+    }
+
+    fn spacing(&self) -> Vec3 {
+        let [nx, ny, nz] = self.resolution;
+        (self.max - self.min) / vec3!(nx - 1, ny - 1, nz - 1)
+    }
+
+    fn node(&self, i: usize, j: usize, k: usize) -> Vec3 {
+        self.min + self.spacing() * vec3!(i, j, k)
+    }
+
+    fn index(&self, i: usize, j: usize, k: usize) -> usize {
+        i + self.resolution[0] * (j + self.resolution[1] * k)
+    }
+
+    // Node value with the indices clamped to the grid
+    fn at(&self, i: i64, j: i64, k: i64) -> Real {
+        let clamp = |v: i64, n: usize| v.max(0).min(n as i64 - 1) as usize;
+        let [nx, ny, nz] = self.resolution;
+        self.data[self.index(clamp(i, nx), clamp(j, ny), clamp(k, nz))]
+    }
+
+    fn trilinear<S: Scalar>(&self, cell: [i64; 3], t: Vec3<S>) -> S {
+        let [i, j, k] = cell;
+        let at = |i, j, k| S::from_real(self.at(i, j, k));
+        let lerp = |a: S, b: S, t: S| a + (b - a) * t;
+        let x00 = lerp(at(i, j, k), at(i + 1, j, k), t.x);
+        let x10 = lerp(at(i, j + 1, k), at(i + 1, j + 1, k), t.x);
+        let x01 = lerp(at(i, j, k + 1), at(i + 1, j, k + 1), t.x);
+        let x11 = lerp(at(i, j + 1, k + 1), at(i + 1, j + 1, k + 1), t.x);
+        lerp(lerp(x00, x10, t.y), lerp(x01, x11, t.y), t.z)
+    }
+
+    fn tricubic<S: Scalar>(&self, cell: [i64; 3], t: Vec3<S>) -> S {
+        let [i, j, k] = cell;
+        let at = |i, j, k| S::from_real(self.at(i, j, k));
+        let c = S::from_real;
+        let cubic = |p: [S; 4], t: S| {
+            p[1] + c(0.5) * t * (p[2] - p[0]
+                + t * (c(2.0) * p[0] - c(5.0) * p[1] + c(4.0) * p[2] - p[3]
+                + t * (c(3.0) * (p[1] - p[2]) + p[3] - p[0])))
+        };
+        let mut planes = [c(0.0); 4];
+        for (dz, plane) in planes.iter_mut().enumerate() {
+            let mut rows = [c(0.0); 4];
+            for (dy, row) in rows.iter_mut().enumerate() {
+                let (y, z) = (j + dy as i64 - 1, k + dz as i64 - 1);
+                *row = cubic([at(i - 1, y, z), at(i, y, z), at(i + 1, y, z), at(i + 2, y, z)], t.x);
+            }
+            *plane = cubic(rows, t.y);
+        }
+        cubic(planes, t.z)
+    }
+
+    // The cell is picked from p, the plain value of z
+    fn distance<S: Scalar>(&self, z: &Vec3<S>, p: &Vec3) -> S {
+        // outside the bounds, the object is at least as far as the box, and
+        // no closer than the value at the box's surface minus the way there
+        let (min, max) = (self.min.lift(), self.max.lift());
+        let clamped = z.clamp_vec(&min, &max);
+        let outside = (*z - clamped).len();
+        let g = (p.clamp_vec(&self.min, &self.max) - self.min) / self.spacing();
+        let [nx, ny, nz] = self.resolution;
+        // the last node has no cell after it, so step back into the one before
+        let floor = |v: Real, n: usize| (v.floor() as i64).min(n as i64 - 2);
+        let cell = [floor(g.x, nx), floor(g.y, ny), floor(g.z, nz)];
+        let t = (clamped - min) / self.spacing().lift() - vec3!(cell[0], cell[1], cell[2]).lift();
+        let inside = match self.interpolation {
+            Interpolation::Trilinear => self.trilinear(cell, t),
+            Interpolation::Tricubic => self.tricubic(cell, t)
+        };
+        S::from_real(0.0).select_lt(outside, outside.max(inside - outside), inside)
+    }
+}
+
+impl Object for SdfGrid {
+    fn distance_estimator(&self, z: &Vec3) -> Real {
+        self.distance(z, z)
+    }
+
+    fn distance_dual(&self, z: &Vec3<Dual3>) -> Dual3 {
+        self.distance(z, &Dual3::values(z))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::objects::Sphere;
+    use crate::objects::base::tests::check_gradient;
+
+    fn baked_sphere() -> SdfGrid {
+        SdfGrid::bake(&Sphere::new(1.0), &vec3!(-2), &vec3!(2), [17, 17, 17])
+    }
+
+    #[test]
+    fn exact_on_nodes() {
+        let grid = baked_sphere();
+        let sphere = Sphere::new(1.0);
+        for p in [vec3!(0), vec3!(0.5, -1, 2), vec3!(2, 2, 2), vec3!(-2, 0.25, 1.75)].iter() {
+            assert!((grid.distance_estimator(p) - sphere.distance_estimator(p)).abs() < 1e-12);
+        }
+    }
+
+    #[test]
+    fn tricubic_beats_trilinear() {
+        let trilinear = baked_sphere();
+        let tricubic = baked_sphere().with_interpolation(Interpolation::Tricubic);
+        let sphere = Sphere::new(1.0);
+        let (mut linear_error, mut cubic_error) = (0.0 as Real, 0.0 as Real);
+        for i in 0..50 {
+            let p = vec3!((i as Real * 0.37).sin(), (i as Real * 0.91).cos(), (i as Real * 0.13).sin()) * 1.3;
+            linear_error = linear_error.max((trilinear.distance_estimator(&p) - sphere.distance_estimator(&p)).abs());
+            cubic_error = cubic_error.max((tricubic.distance_estimator(&p) - sphere.distance_estimator(&p)).abs());
+        }
+        assert!(linear_error < 0.05);
+        assert!(cubic_error < linear_error);
+    }
+
+    #[test]
+    fn exact_gradients() {
+        for grid in [baked_sphere(), baked_sphere().with_interpolation(Interpolation::Tricubic)].iter() {
+            check_gradient(grid, &vec3!(0.61, 0.37, -0.13));
+            check_gradient(grid, &vec3!(2.6, 0.37, -2.9));
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "max above min")]
+    fn bounds_must_not_be_flat() {
+        SdfGrid::bake(&Sphere::new(1.0), &vec3!(-2), &vec3!(2, 2, -2), [4, 4, 4]);
+    }
+
+    #[test]
+    fn outside_the_bounds() {
+        let grid = baked_sphere();
+        // the distance to the box
+        assert!((grid.distance_estimator(&vec3!(5, 0, 0)) - 3.0).abs() < 1e-12);
+        // the value at the face minus the way there
+        assert!((grid.distance_estimator(&vec3!(2.5, 0, 0)) - 0.5).abs() < 1e-12);
+    }
+
+    #[test]
+    fn never_overestimates_outside_the_bounds() {
+        let grid = baked_sphere();
+        let sphere = Sphere::new(1.0);
+        for i in 0..200 {
+            let t = i as Real;
+            let p = vec3!((t * 0.37).sin(), (t * 0.91).cos(), (t * 0.13).sin()).normalized() * (2.5 + 0.02 * t);
+            // anything over the exact distance is down to interpolating at the face
+            let face = p.clamp(-2.0, 2.0);
+            let error = (grid.distance_estimator(&face) - sphere.distance_estimator(&face)).abs();
+            assert!(grid.distance_estimator(&p) <= sphere.distance_estimator(&p) + error + 1e-12);
+        }
+        assert!(grid.distance_estimator(&vec3!(5, 1, 0)) <= sphere.distance_estimator(&vec3!(5, 1, 0)));
+    }
+}
@@ -0,0 +1,147 @@
+use std::path::Path;
+use image::{DynamicImage, ImageResult};
+use crate::linalg::{Real, Dual3, Vec2, Vec3};
+use crate::objects::base::Object;
+use crate::objects::displace::Texture;
+
+// Terrain height above the xz-plane, looked up with p = (x, z).
+// The slope is for normals, and falls back to central differences
+pub trait HeightMap {
+    fn height(&self, p: &Vec2) -> Real;
+
+    fn slope(&self, p: &Vec2) -> Vec2 {
+        let h = Real::EPSILON.cbrt();
+        let diff = |e: Vec2| (self.height(&(*p + e)) - self.height(&(*p - e))) / (2.0 * h);
+        vec2!(diff(vec2!(h, 0)), diff(vec2!(0, h)))
+    }
+}
+
+impl<F: Fn(&Vec2) -> Real> HeightMap for F {
+    fn height(&self, p: &Vec2) -> Real {
+        self(p)
+    }
+}
+
+// A grayscale image stretched over size, centered on the origin, with white
+// at the given height. It repeats outside its footprint
+pub struct HeightImage {
+    texture: Texture,
+    size: Vec2,
+    height: Real
+}
+
+impl HeightImage {
+    pub fn new(image: &DynamicImage, size: &Vec2, height: Real) -> Self {
+        Self {texture: Texture::new(image), size: *size, height}
+    }
+
+    pub fn open<P: AsRef<Path>>(path: P, size: &Vec2, height: Real) -> ImageResult<Self> {
+        Ok(Self::new(&image::open(path)?, size, height))
+    }
+
+    // The steepest slope bilinear filtering can produce, which is the
+    // largest jump between neighbouring texels over the texel size
+    pub fn lipschitz(&self) -> Real {
+        let (width, height) = self.texture.dimensions();
+        let (mut du, mut dv) = (0.0 as Real, 0.0 as Real);
+        for y in 0..height as i64 {
+            for x in 0..width as i64 {
+                let t = self.texture.texel(x, y);
+                du = du.max((self.texture.texel(x + 1, y) - t).abs());
+                dv = dv.max((self.texture.texel(x, y + 1) - t).abs());
+            }
+        }
+        let du = du * width as Real / self.size.x;
+        let dv = dv * height as Real / self.size.y;
+        self.height * (du * du + dv * dv).sqrt()
+    }
+}
+
+impl HeightMap for HeightImage {
+    fn height(&self, p: &Vec2) -> Real {
+        self.height * self.texture.lookup(&(*p / self.size + 0.5))
+    }
+
+    fn slope(&self, p: &Vec2) -> Vec2 {
+        self.texture.lookup_gradient(&(*p / self.size + 0.5)) / self.size * self.height
+    }
+}
+
+// The vertical distance to a heightmap overestimates the true distance
+// on slopes. Scaling it by 1 / sqrt(1 + L^2), L bounding the slope of the map,
+// makes it safe to march
+pub struct Heightfield<H: HeightMap> {
+    map: H,
+    step_scale: Real
+}
+
+impl<H: HeightMap> Heightfield<H> {
+    pub fn new(map: H, lipschitz: Real) -> Self {
+        Self {map, step_scale: 1.0 / (1.0 + lipschitz * lipschitz).sqrt()}
+    }
+
+    // The fraction of the vertical distance that is safe to step
+    pub fn step_scale(&self) -> Real {
+        self.step_scale
+    }
+}
+
+impl Heightfield<HeightImage> {
+    pub fn from_image(image: HeightImage) -> Self {
+        let lipschitz = image.lipschitz();
+        Self::new(image, lipschitz)
+    }
+}
+
+impl<H: HeightMap> Object for Heightfield<H> {
+    fn distance_estimator(&self, z: &Vec3) -> Real {
+        (z.y - self.map.height(&z.xz())) * self.step_scale
+    }
+
+    fn distance_dual(&self, z: &Vec3<Dual3>) -> Dual3 {
+        let p = Dual3::values(z);
+        let slope = self.map.slope(&p.xz());
+        Dual3::through(z, self.distance_estimator(&p), &(vec3!(-slope.x, 1, -slope.y) * self.step_scale))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::{GrayImage, Luma};
+    use crate::objects::base::tests::check_gradient;
+
+    #[test]
+    fn flat_ground() {
+        let ground = Heightfield::new(|_: &Vec2| 1.0, 0.0);
+        assert_eq!(ground.step_scale(), 1.0);
+        assert_eq!(ground.distance_estimator(&vec3!(5, 3, -2)), 2.0);
+        assert_eq!(ground.distance_estimator(&vec3!(0, 0, 0)), -1.0);
+    }
+
+    #[test]
+    fn slope_never_overestimates() {
+        // a 45 degree ramp, where the true distance is the vertical one over sqrt(2)
+        let ramp = Heightfield::new(|p: &Vec2| p.x, 1.0);
+        let d = ramp.distance_estimator(&vec3!(0, 1, 0));
+        assert!((d - Real::sqrt(0.5)).abs() < 1e-12);
+    }
+
+    #[test]
+    fn exact_gradients() {
+        let image = GrayImage::from_fn(4, 3, |x, y| Luma([(x * 50 + y * 70) as u8]));
+        let terrain = Heightfield::from_image(HeightImage::new(&DynamicImage::ImageLuma8(image), &vec2!(8, 6), 2.0));
+        check_gradient(&terrain, &vec3!(0.7, 1.5, -0.4));
+        check_gradient(&Heightfield::new(|p: &Vec2| p.x * p.y, 2.0), &vec3!(0.7, 1.5, -0.4));
+    }
+
+    #[test]
+    fn image_lipschitz() {
+        let image = GrayImage::from_fn(4, 4, |x, _| Luma([if x < 2 { 0 } else { 255 }]));
+        let image = HeightImage::new(&DynamicImage::ImageLuma8(image), &vec2!(8, 8), 3.0);
+        // full height over one texel of 2 world units
+        assert_eq!(image.lipschitz(), 1.5);
+        let terrain = Heightfield::from_image(image);
+        assert_eq!(terrain.step_scale(), 1.0 / Real::sqrt(3.25));
+    }
+}
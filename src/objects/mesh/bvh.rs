@@ -0,0 +1,137 @@
+use crate::linalg::{Real, Vec3};
+
+const LEAF_SIZE: usize = 4;
+
+fn min3(a: &Vec3, b: &Vec3) -> Vec3 {
+    vec3!(a.x.min(b.x), a.y.min(b.y), a.z.min(b.z))
+}
+
+fn max3(a: &Vec3, b: &Vec3) -> Vec3 {
+    vec3!(a.x.max(b.x), a.y.max(b.y), a.z.max(b.z))
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Aabb {
+    pub min: Vec3,
+    pub max: Vec3
+}
+
+impl Aabb {
+    pub fn empty() -> Self {
+        Self {min: vec3!(1./0.), max: vec3!(-1./0.)}
+    }
+
+    pub fn around(points: &[Vec3]) -> Self {
+        points.iter().fold(Self::empty(), |b, p| b.grow(&Self {min: *p, max: *p}))
+    }
+
+    pub fn grow(&self, other: &Self) -> Self {
+        Self {min: min3(&self.min, &other.min), max: max3(&self.max, &other.max)}
+    }
+
+    pub fn center(&self) -> Vec3 {
+        (self.min + self.max) * 0.5
+    }
+
+    pub fn distance_squared(&self, p: &Vec3) -> Real {
+        let d = max3(&(self.min - *p), &(*p - self.max)).max(0.0);
+        d.dot(&d)
+    }
+}
+
+enum Node {
+    Leaf {bounds: Aabb, start: usize, end: usize},
+    Branch {bounds: Aabb, left: usize, right: usize}
+}
+
+impl Node {
+    fn bounds(&self) -> &Aabb {
+        match self {
+            Node::Leaf {bounds, ..} | Node::Branch {bounds, ..} => bounds
+        }
+    }
+}
+
+// Bounding volume hierarchy over anything with a bounding box,
+// split at the median along the longest axis
+pub struct Bvh {
+    nodes: Vec<Node>,
+    order: Vec<usize>
+}
+
+impl Bvh {
+    pub fn new(items: &[Aabb]) -> Self {
+        let mut bvh = Self {nodes: vec![], order: (0..items.len()).collect()};
+        if !items.is_empty() {
+            bvh.build(items, 0, items.len());
+        }
+        bvh
+    }
+
+    fn build(&mut self, items: &[Aabb], start: usize, end: usize) -> usize {
+        let bounds = self.order[start..end].iter()
+            .fold(Aabb::empty(), |b, &i| b.grow(&items[i]));
+        let index = self.nodes.len();
+        if end - start <= LEAF_SIZE {
+            self.nodes.push(Node::Leaf {bounds, start, end});
+            return index;
+        }
+        let centers = self.order[start..end].iter()
+            .fold(Aabb::empty(), |b, &i| b.grow(&Aabb {min: items[i].center(), max: items[i].center()}));
+        let size = centers.max - centers.min;
+        let axis = |b: &Aabb| {
+            let c = b.center();
+            if size.x >= size.y && size.x >= size.z { c.x } else if size.y >= size.z { c.y } else { c.z }
+        };
+        self.order[start..end].sort_by(|&a, &b| axis(&items[a]).total_cmp(&axis(&items[b])));
+        // reserve our slot before the children take theirs
+        self.nodes.push(Node::Leaf {bounds, start, end});
+        let middle = (start + end) / 2;
+        let left = self.build(items, start, middle);
+        let right = self.build(items, middle, end);
+        self.nodes[index] = Node::Branch {bounds, left, right};
+        index
+    }
+
+    // Finds the item closest to p, given the squared distance to each item.
+    // Returns its index and that squared distance
+    pub fn nearest<F: FnMut(usize) -> Real>(&self, p: &Vec3, mut distance_squared: F) -> Option<(usize, Real)> {
+        let mut best: Option<(usize, Real)> = None;
+        let mut stack = if self.nodes.is_empty() { vec![] } else { vec![0] };
+        while let Some(index) = stack.pop() {
+            let node = &self.nodes[index];
+            if let Some((_, d)) = best {
+                if node.bounds().distance_squared(p) >= d {
+                    continue;
+                }
+            }
+            match node {
+                Node::Leaf {start, end, ..} => {
+                    for &item in self.order[*start..*end].iter() {
+                        let d = distance_squared(item);
+                        let closer = match best {
+                            Some((_, b)) => d < b,
+                            None => true
+                        };
+                        if closer {
+                            best = Some((item, d));
+                        }
+                    }
+                }
+                Node::Branch {left, right, ..} => {
+                    // push the far child first so the near one is searched first
+                    let dl = self.nodes[*left].bounds().distance_squared(p);
+                    let dr = self.nodes[*right].bounds().distance_squared(p);
+                    if dl < dr {
+                        stack.push(*right);
+                        stack.push(*left);
+                    } else {
+                        stack.push(*left);
+                        stack.push(*right);
+                    }
+                }
+            }
+        }
+        best
+    }
+}
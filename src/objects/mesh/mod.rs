@@ -0,0 +1,376 @@
+pub mod bvh;
+pub mod obj;
+pub mod stl;
+
+use std::collections::HashMap;
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader};
+use std::path::Path;
+use crate::linalg::{Real, Dual3, Vec3};
+use crate::objects::base::Object;
+use self::bvh::{Aabb, Bvh};
+
+#[derive(Debug)]
+pub enum MeshError {
+    Io(io::Error),
+    Parse(String)
+}
+
+impl fmt::Display for MeshError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            MeshError::Io(e) => write!(f, "could not read mesh: {}", e),
+            MeshError::Parse(msg) => write!(f, "could not parse mesh: {}", msg)
+        }
+    }
+}
+
+impl std::error::Error for MeshError {}
+
+impl From<io::Error> for MeshError {
+    fn from(e: io::Error) -> Self {
+        MeshError::Io(e)
+    }
+}
+
+// Which part of a triangle a closest point lies on, by corner index
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Feature {
+    Vertex(usize),
+    Edge(usize, usize),
+    Face
+}
+
+// From Real-Time Collision Detection by Christer Ericson, working out which
+// Voronoi region of the triangle p is in
+fn closest_point(p: &Vec3, a: &Vec3, b: &Vec3, c: &Vec3) -> (Vec3, Feature) {
+    let ab = *b - *a;
+    let ac = *c - *a;
+    let ap = *p - *a;
+    let d1 = ab.dot(&ap);
+    let d2 = ac.dot(&ap);
+    if d1 <= 0.0 && d2 <= 0.0 {
+        return (*a, Feature::Vertex(0));
+    }
+    let bp = *p - *b;
+    let d3 = ab.dot(&bp);
+    let d4 = ac.dot(&bp);
+    if d3 >= 0.0 && d4 <= d3 {
+        return (*b, Feature::Vertex(1));
+    }
+    let vc = d1 * d4 - d3 * d2;
+    if vc <= 0.0 && d1 >= 0.0 && d3 <= 0.0 {
+        return (*a + ab * (d1 / (d1 - d3)), Feature::Edge(0, 1));
+    }
+    let cp = *p - *c;
+    let d5 = ab.dot(&cp);
+    let d6 = ac.dot(&cp);
+    if d6 >= 0.0 && d5 <= d6 {
+        return (*c, Feature::Vertex(2));
+    }
+    let vb = d5 * d2 - d1 * d6;
+    if vb <= 0.0 && d2 >= 0.0 && d6 <= 0.0 {
+        return (*a + ac * (d2 / (d2 - d6)), Feature::Edge(0, 2));
+    }
+    let va = d3 * d6 - d5 * d4;
+    if va <= 0.0 && d4 - d3 >= 0.0 && d5 - d6 >= 0.0 {
+        let w = (d4 - d3) / ((d4 - d3) + (d5 - d6));
+        return (*b + (*c - *b) * w, Feature::Edge(1, 2));
+    }
+    let denom = 1.0 / (va + vb + vc);
+    (*a + ab * (vb * denom) + ac * (vc * denom), Feature::Face)
+}
+
+fn edge_key(a: usize, b: usize) -> (usize, usize) {
+    (a.min(b), a.max(b))
+}
+
+// A closed triangle mesh with exact signed distance. The sign is taken from
+// the angle weighted pseudo-normal of the closest vertex, edge or face
+// (Baerentzen and Aanaes), so the mesh should be watertight and consistently
+// wound counter-clockwise seen from outside
+pub struct Mesh {
+    vertices: Vec<Vec3>,
+    triangles: Vec<[usize; 3]>,
+    face_normals: Vec<Vec3>,
+    vertex_normals: Vec<Vec3>,
+    edge_normals: HashMap<(usize, usize), Vec3>,
+    bvh: Bvh
+}
+
+impl Mesh {
+    pub fn new(vertices: Vec<Vec3>, triangles: Vec<[usize; 3]>) -> Result<Self, MeshError> {
+        if let Some(t) = triangles.iter().find(|t| t.iter().any(|&i| i >= vertices.len())) {
+            return Err(MeshError::Parse(format!(
+                "triangle {:?} refers past the last of {} vertices", t, vertices.len()
+            )));
+        }
+        Ok(Self::build(vertices, triangles))
+    }
+
+    // Every index of the triangles must be a vertex
+    fn build(vertices: Vec<Vec3>, triangles: Vec<[usize; 3]>) -> Self {
+        let mut face_normals = Vec::with_capacity(triangles.len());
+        let mut vertex_normals = vec![vec3!(0); vertices.len()];
+        let mut edge_normals = HashMap::new();
+        for t in triangles.iter() {
+            let [a, b, c] = [vertices[t[0]], vertices[t[1]], vertices[t[2]]];
+            let n = (b - a).cross(&(c - a));
+            // degenerate triangles get no say in the sign
+            let n = if n.len() > 0.0 { n.normalized() } else { vec3!(0) };
+            face_normals.push(n);
+            for i in 0..3 {
+                let corner = vertices[t[i]];
+                let e1 = vertices[t[(i + 1) % 3]] - corner;
+                let e2 = vertices[t[(i + 2) % 3]] - corner;
+                let angle = (e1.dot(&e2) / (e1.len() * e2.len())).clamp(-1.0, 1.0).acos();
+                if angle.is_finite() {
+                    vertex_normals[t[i]] += n * angle;
+                }
+                *edge_normals.entry(edge_key(t[i], t[(i + 1) % 3])).or_insert(vec3!(0)) += n;
+            }
+        }
+        let bounds: Vec<Aabb> = triangles.iter()
+            .map(|t| Aabb::around(&[vertices[t[0]], vertices[t[1]], vertices[t[2]]]))
+            .collect();
+        let bvh = Bvh::new(&bounds);
+        Self {vertices, triangles, face_normals, vertex_normals, edge_normals, bvh}
+    }
+
+    // Builds a mesh from loose triangle corners, three per triangle,
+    // merging corners at identical positions
+    pub fn from_corners(corners: &[Vec3]) -> Self {
+        let mut index = HashMap::new();
+        let mut vertices = vec![];
+        let mut weld = |p: &Vec3| {
+            let key = [p.x.to_bits(), p.y.to_bits(), p.z.to_bits()];
+            *index.entry(key).or_insert_with(|| {
+                vertices.push(*p);
+                vertices.len() - 1
+            })
+        };
+        let triangles = corners.chunks(3)
+            .map(|c| [weld(&c[0]), weld(&c[1]), weld(&c[2])])
+            .collect();
+        Self::build(vertices, triangles)
+    }
+
+    pub fn from_obj<R: BufRead>(reader: R) -> Result<Self, MeshError> {
+        let (vertices, triangles) = obj::parse(reader)?;
+        Self::new(vertices, triangles)
+    }
+
+    pub fn from_stl(bytes: &[u8]) -> Result<Self, MeshError> {
+        Ok(Self::from_corners(&stl::parse(bytes)?))
+    }
+
+    // Picks the format from the file extension
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, MeshError> {
+        let path = path.as_ref();
+        let extension = path.extension()
+            .and_then(|e| e.to_str())
+            .map(|e| e.to_lowercase());
+        match extension.as_deref() {
+            Some("obj") => Self::from_obj(BufReader::new(File::open(path)?)),
+            Some("stl") => Self::from_stl(&std::fs::read(path)?),
+            _ => Err(MeshError::Parse(format!("unknown mesh format {}", path.display())))
+        }
+    }
+
+    pub fn triangle_count(&self) -> usize {
+        self.triangles.len()
+    }
+
+    fn corners(&self, triangle: usize) -> [Vec3; 3] {
+        let t = self.triangles[triangle];
+        [self.vertices[t[0]], self.vertices[t[1]], self.vertices[t[2]]]
+    }
+}
+
+impl Mesh {
+    // The closest point on the surface, and which side of it z is on
+    fn closest(&self, z: &Vec3) -> Option<(Vec3, Real)> {
+        let nearest = self.bvh.nearest(z, |i| {
+            let [a, b, c] = self.corners(i);
+            let (q, _) = closest_point(z, &a, &b, &c);
+            (*z - q).dot(&(*z - q))
+        });
+        let (triangle, _) = nearest?;
+        let [a, b, c] = self.corners(triangle);
+        let (q, feature) = closest_point(z, &a, &b, &c);
+        let t = self.triangles[triangle];
+        let normal = match feature {
+            Feature::Vertex(i) => self.vertex_normals[t[i]],
+            Feature::Edge(i, j) => self.edge_normals[&edge_key(t[i], t[j])],
+            Feature::Face => self.face_normals[triangle]
+        };
+        Some((q, if (*z - q).dot(&normal) < 0.0 { -1.0 } else { 1.0 }))
+    }
+}
+
+impl Object for Mesh {
+    fn distance_estimator(&self, z: &Vec3) -> Real {
+        match self.closest(z) {
+            Some((q, sign)) => (*z - q).len() * sign,
+            None => 1./0.
+        }
+    }
+
+    fn distance_dual(&self, z: &Vec3<Dual3>) -> Dual3 {
+        match self.closest(&Dual3::values(z)) {
+            Some((q, sign)) => (*z - q.lift()).len() * Dual3::constant(sign),
+            None => Dual3::constant(1./0.)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::objects::{Cube, Sphere, Subtract};
+    use crate::linalg::Number;
+    use crate::objects::base::tests::check_gradient;
+
+    const CUBE_OBJ: &str = "
+        # unit cube, quads wound counter-clockwise from outside
+        v -1 -1 -1
+        v 1 -1 -1
+        v 1 1 -1
+        v -1 1 -1
+        v -1 -1 1
+        v 1 -1 1
+        v 1 1 1
+        v -1 1 1
+        f 1 4 3 2
+        f 5 6 7 8
+        f 1 2 6 5
+        f 3 4 8 7
+        f 2 3 7 6
+        f 1//1 5//1 8//1 4//1
+    ";
+
+    fn points() -> Vec<Vec3> {
+        vec![
+            vec3!(0), vec3!(0.5, 0.2, -0.3), vec3!(2, 0, 0), vec3!(2, 2, 2),
+            vec3!(-3, 1.5, 0.5), vec3!(0.9, 0.9, 0.9), vec3!(1.5, -1.5, 0.2)
+        ]
+    }
+
+    #[test]
+    fn obj_cube_matches_analytic_cube() {
+        let mesh = Mesh::from_obj(CUBE_OBJ.as_bytes()).unwrap();
+        assert_eq!(mesh.triangle_count(), 12);
+        let cube = Cube::new(&vec3!(1));
+        for p in points() {
+            assert!((mesh.distance_estimator(&p) - cube.distance_estimator(&p)).abs() < 1e-12, "{:?}", p);
+        }
+    }
+
+    #[test]
+    fn exact_gradients() {
+        let mesh = Mesh::from_obj(CUBE_OBJ.as_bytes()).unwrap();
+        for p in [vec3!(0.5, 0.2, -0.3), vec3!(2, 0.5, 0), vec3!(2, 2, 2), vec3!(1.5, -1.5, 0.2)].iter() {
+            check_gradient(&mesh, p);
+        }
+    }
+
+    #[test]
+    fn nan_vertices_dont_panic() {
+        // a triangle with no finite corner has a NaN center in the BVH
+        let broken = format!("{}\nv nan nan nan\nv nan nan nan\nv nan nan nan\nf 9 10 11\n", CUBE_OBJ);
+        let mesh = Mesh::from_obj(broken.as_bytes()).unwrap();
+        mesh.distance_estimator(&vec3!(2, 0, 0));
+    }
+
+    #[test]
+    fn stl_ascii_and_binary_agree() {
+        let mesh = Mesh::from_obj(CUBE_OBJ.as_bytes()).unwrap();
+        let mut ascii = String::from("solid cube\n");
+        let mut binary = vec![0u8; 80];
+        binary.extend(&(mesh.triangle_count() as u32).to_le_bytes());
+        for i in 0..mesh.triangle_count() {
+            ascii.push_str("facet normal 0 0 0\nouter loop\n");
+            binary.extend(&[0u8; 12]);
+            for p in mesh.corners(i).iter() {
+                ascii.push_str(&format!("vertex {} {} {}\n", p.x, p.y, p.z));
+                for c in [p.x, p.y, p.z].iter() {
+                    binary.extend(&(c.to_f64() as f32).to_le_bytes());
+                }
+            }
+            ascii.push_str("endloop\nendfacet\n");
+            binary.extend(&[0u8; 2]);
+        }
+        ascii.push_str("endsolid cube\n");
+        let from_ascii = Mesh::from_stl(ascii.as_bytes()).unwrap();
+        let from_binary = Mesh::from_stl(&binary).unwrap();
+        binary.extend(&[0u8; 16]);
+        let padded = Mesh::from_stl(&binary).unwrap();
+        assert_eq!(padded.vertices.len(), 8);
+        // welding brings the corners back down to eight vertices
+        assert_eq!(from_ascii.vertices.len(), 8);
+        assert_eq!(from_binary.vertices.len(), 8);
+        for p in points() {
+            assert_eq!(from_ascii.distance_estimator(&p), mesh.distance_estimator(&p));
+            assert_eq!(from_binary.distance_estimator(&p), mesh.distance_estimator(&p));
+        }
+    }
+
+    #[test]
+    fn bvh_agrees_with_brute_force() {
+        // a finely tessellated sphere, enough triangles for a deep tree
+        let (rings, segments) = (12, 24);
+        let mut vertices = vec![vec3!(0, 1, 0), vec3!(0, -1, 0)];
+        for i in 1..rings {
+            let theta = crate::linalg::consts::PI * i as Real / rings as Real;
+            for j in 0..segments {
+                let phi = 2.0 * crate::linalg::consts::PI * j as Real / segments as Real;
+                vertices.push(vec3!(theta.sin() * phi.cos(), theta.cos(), theta.sin() * phi.sin()));
+            }
+        }
+        let ring = |i: usize, j: usize| 2 + (i - 1) * segments + j % segments;
+        let mut triangles = vec![];
+        for j in 0..segments {
+            triangles.push([0, ring(1, j + 1), ring(1, j)]);
+            triangles.push([1, ring(rings - 1, j), ring(rings - 1, j + 1)]);
+            for i in 1..rings - 1 {
+                triangles.push([ring(i, j), ring(i, j + 1), ring(i + 1, j)]);
+                triangles.push([ring(i, j + 1), ring(i + 1, j + 1), ring(i + 1, j)]);
+            }
+        }
+        let mesh = Mesh::new(vertices, triangles).unwrap();
+        for p in points() {
+            let brute = (0..mesh.triangle_count())
+                .map(|i| {
+                    let [a, b, c] = mesh.corners(i);
+                    (p - closest_point(&p, &a, &b, &c).0).len()
+                })
+                .fold(1./0., Real::min);
+            assert!((mesh.distance_estimator(&p).abs() - brute).abs() < 1e-12);
+            // and it's a decent sphere, with the right sign
+            assert!((mesh.distance_estimator(&p) - Sphere::new(1.0).distance_estimator(&p)).abs() < 0.05);
+        }
+    }
+
+    #[test]
+    fn csg_with_primitives() {
+        let mesh = Mesh::from_obj(CUBE_OBJ.as_bytes()).unwrap();
+        let carved = Subtract(mesh, Sphere::new(1.2));
+        assert!(carved.distance_estimator(&vec3!(0)) > 0.0);
+        assert!(carved.distance_estimator(&vec3!(0.95, 0.95, 0.95)) < 0.0);
+    }
+
+    #[test]
+    fn triangles_must_index_vertices() {
+        let vertices = vec![vec3!(0), vec3!(1, 0, 0), vec3!(0, 1, 0)];
+        assert!(Mesh::new(vertices.clone(), vec![[0, 1, 2]]).is_ok());
+        assert!(Mesh::new(vertices, vec![[0, 1, 3]]).is_err());
+    }
+
+    #[test]
+    fn bad_input() {
+        assert!(Mesh::from_obj("v 1 2\n".as_bytes()).is_err());
+        assert!(Mesh::from_obj("v 1 2 3\nf 1 2 3\n".as_bytes()).is_err());
+        assert!(Mesh::open("mesh.ply").is_err());
+    }
+}
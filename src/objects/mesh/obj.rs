@@ -0,0 +1,49 @@
+use std::io::BufRead;
+use crate::linalg::Vec3;
+use crate::objects::mesh::MeshError;
+
+// Reads the vertices and faces of a Wavefront OBJ file, ignoring everything
+// else. Polygons are split into fans of triangles
+pub fn parse<R: BufRead>(reader: R) -> Result<(Vec<Vec3>, Vec<[usize; 3]>), MeshError> {
+    let mut vertices = vec![];
+    let mut triangles = vec![];
+    for (number, line) in reader.lines().enumerate() {
+        let line = line?;
+        let error = |msg: &str| MeshError::Parse(format!("line {}: {}", number + 1, msg));
+        let mut tokens = line.split_whitespace();
+        match tokens.next() {
+            Some("v") => {
+                let mut coords = [0.0; 3];
+                for c in coords.iter_mut() {
+                    *c = tokens.next()
+                        .and_then(|t| t.parse().ok())
+                        .ok_or_else(|| error("expected three coordinates"))?;
+                }
+                vertices.push(vec3!(coords[0], coords[1], coords[2]));
+            }
+            Some("f") => {
+                let mut face = vec![];
+                for token in tokens {
+                    // faces can be v, v/vt, v//vn or v/vt/vn
+                    let index: i64 = token.split('/').next()
+                        .and_then(|t| t.parse().ok())
+                        .ok_or_else(|| error("bad face index"))?;
+                    // indices count from 1, negative ones from the end
+                    let index = if index < 0 { vertices.len() as i64 + index } else { index - 1 };
+                    if index < 0 || index as usize >= vertices.len() {
+                        return Err(error("face index out of range"));
+                    }
+                    face.push(index as usize);
+                }
+                if face.len() < 3 {
+                    return Err(error("face with less than three vertices"));
+                }
+                for i in 1..face.len() - 1 {
+                    triangles.push([face[0], face[i], face[i + 1]]);
+                }
+            }
+            _ => {}
+        }
+    }
+    Ok((vertices, triangles))
+}
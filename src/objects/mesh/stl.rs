@@ -0,0 +1,55 @@
+use std::convert::TryInto;
+use crate::linalg::{Real, Vec3};
+use crate::objects::mesh::MeshError;
+
+// STL stores every triangle with its own three corners, so this returns
+// a flat list of corners, three per triangle
+pub fn parse(bytes: &[u8]) -> Result<Vec<Vec3>, MeshError> {
+    // plenty of binary files start with "solid" too, so trust the size instead.
+    // Some writers pad the end, so only require the triangles to fit. Text
+    // can't pass for that, as four printable bytes count hundreds of millions
+    if bytes.len() >= 84 {
+        let count = u32::from_le_bytes(bytes[80..84].try_into().unwrap()) as usize;
+        if bytes.len() >= 84 + count * 50 {
+            return Ok(parse_binary(&bytes[84..], count));
+        }
+    }
+    parse_ascii(bytes)
+}
+
+fn parse_binary(data: &[u8], count: usize) -> Vec<Vec3> {
+    let float = |at: usize| f32::from_le_bytes(data[at..at + 4].try_into().unwrap()) as Real;
+    let mut corners = Vec::with_capacity(count * 3);
+    for triangle in 0..count {
+        // skip the 12 byte normal, and the attribute count at the end
+        let start = triangle * 50 + 12;
+        for corner in 0..3 {
+            let at = start + corner * 12;
+            corners.push(vec3!(float(at), float(at + 4), float(at + 8)));
+        }
+    }
+    corners
+}
+
+fn parse_ascii(bytes: &[u8]) -> Result<Vec<Vec3>, MeshError> {
+    let text = std::str::from_utf8(bytes)
+        .map_err(|_| MeshError::Parse("neither binary nor text STL".to_string()))?;
+    let mut corners = vec![];
+    let mut tokens = text.split_whitespace();
+    while let Some(token) = tokens.next() {
+        if token != "vertex" {
+            continue;
+        }
+        let mut coords = [0.0; 3];
+        for c in coords.iter_mut() {
+            *c = tokens.next()
+                .and_then(|t| t.parse().ok())
+                .ok_or_else(|| MeshError::Parse("expected three coordinates after vertex".to_string()))?;
+        }
+        corners.push(vec3!(coords[0], coords[1], coords[2]));
+    }
+    if corners.len() % 3 != 0 {
+        return Err(MeshError::Parse("vertex count is not a multiple of three".to_string()));
+    }
+    Ok(corners)
+}
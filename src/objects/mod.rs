@@ -1,5 +1,24 @@
 pub mod base;
 pub mod primitives;
+#[macro_use]
+pub mod deform;
+pub mod modifiers;
+pub mod displace;
+pub mod fractals;
+pub mod heightfield;
+pub mod shapes2d;
+pub mod curves;
+pub mod mesh;
+pub mod grid;
 
-pub use base::{Scene, Object, Union, Subtract, Intersect};
-pub use primitives::{Sphere, Cube};
\ No newline at end of file
+pub use base::{Scene, Object, Sample, Union, Subtract, Intersect};
+pub use primitives::{Sphere, Cube};
+pub use deform::{Axis, Twist, Bend, Taper, Shear};
+pub use modifiers::{Round, Onion, Elongate, Mirror, Rotate};
+pub use displace::{ScalarField, Displace, Texture, ImageField};
+pub use fractals::{Mandelbulb, Mandelbox, Kifs, MengerSponge, SierpinskiTetrahedron, QuaternionJulia, OrbitTrap};
+pub use heightfield::{HeightMap, HeightImage, Heightfield};
+pub use shapes2d::{Object2D, Circle, Rectangle, RoundedRectangle, RegularPolygon, Arc, Star, Extrude, Revolve};
+pub use curves::{Polygon, Polyline, QuadraticBezier, Curve, Polyline3, QuadraticBezier3, CatmullRom, Tube};
+pub use mesh::{Mesh, MeshError};
+pub use grid::{SdfGrid, Interpolation, DataType};
@@ -0,0 +1,161 @@
+use crate::linalg::{Real, Scalar, Dual3, Packet, Quat, Vec3};
+use crate::objects::base::{Object, Sample};
+
+// Inflates the surface outwards, rounding off edges and corners
+pub struct Round<T: Object>(pub T, pub Real);
+
+impl<T: Object> Object for Round<T> {
+    fn distance_estimator(&self, z: &Vec3) -> Real {
+        self.0.distance_estimator(z) - self.1
+    }
+
+    fn sample(&self, z: &Vec3) -> Sample {
+        self.0.sample(z).map(|d| d - self.1)
+    }
+
+    fn distance_dual(&self, z: &Vec3<Dual3>) -> Dual3 {
+        self.0.distance_dual(z) - Dual3::constant(self.1)
+    }
+
+    fn distance_packet(&self, z: &Vec3<Packet>) -> Packet {
+        self.0.distance_packet(z) - Packet::splat(self.1)
+    }
+}
+
+// Hollows the object into a shell of the given thickness around the surface
+pub struct Onion<T: Object>(pub T, pub Real);
+
+impl<T: Object> Object for Onion<T> {
+    fn distance_estimator(&self, z: &Vec3) -> Real {
+        self.0.distance_estimator(z).abs() - self.1
+    }
+
+    fn sample(&self, z: &Vec3) -> Sample {
+        self.0.sample(z).map(|d| d.abs() - self.1)
+    }
+
+    fn distance_dual(&self, z: &Vec3<Dual3>) -> Dual3 {
+        self.0.distance_dual(z).abs() - Dual3::constant(self.1)
+    }
+
+    fn distance_packet(&self, z: &Vec3<Packet>) -> Packet {
+        self.0.distance_packet(z).abs() - Packet::splat(self.1)
+    }
+}
+
+// Stretches the object by splitting it at the origin and filling the gap,
+// h being the half-length of the gap along each axis
+pub struct Elongate<T: Object>(pub T, pub Vec3);
+
+impl<T: Object> Elongate<T> {
+    fn warp<S: Scalar>(&self, z: &Vec3<S>) -> Vec3<S> {
+        let clamp = |x: S, h: Real| x.max(S::from_real(-h)).min(S::from_real(h));
+        let h = self.1;
+        *z - Vec3::new(clamp(z.x, h.x), clamp(z.y, h.y), clamp(z.z, h.z))
+    }
+}
+
+impl_warped_object!(Elongate, 0);
+
+// Reflects the positive side of the object across the planes perpendicular
+// to the chosen axes, given as flags for x, y and z
+pub struct Mirror<T: Object>(pub T, pub [bool; 3]);
+
+impl<T: Object> Mirror<T> {
+    fn warp<S: Scalar>(&self, z: &Vec3<S>) -> Vec3<S> {
+        let fold = |x: S, on: bool| if on { x.abs() } else { x };
+        Vec3::new(fold(z.x, self.1[0]), fold(z.y, self.1[1]), fold(z.z, self.1[2]))
+    }
+}
+
+impl_warped_object!(Mirror, 0);
+
+// Turns the object around the origin. Rotating doesn't stretch space,
+// so the distance stays exact
+pub struct Rotate<T: Object>(pub T, pub Quat);
+
+impl<T: Object> Object for Rotate<T> {
+    fn distance_estimator(&self, z: &Vec3) -> Real {
+        self.0.distance_estimator(&self.1.conjugate().rotate(z))
+    }
+
+    fn sample(&self, z: &Vec3) -> Sample {
+        self.0.sample(&self.1.conjugate().rotate(z))
+    }
+
+    fn distance_dual(&self, z: &Vec3<Dual3>) -> Dual3 {
+        self.0.distance_dual(&self.1.conjugate().rotate(z))
+    }
+
+    fn distance_packet(&self, z: &Vec3<Packet>) -> Packet {
+        self.0.distance_packet(&self.1.conjugate().rotate(z))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::linalg::approx::EPSILON;
+    use crate::objects::{Sphere, Cube};
+
+    // A unit sphere away from the origin
+    struct Offset(Vec3);
+
+    impl Object for Offset {
+        fn distance_estimator(&self, z: &Vec3) -> Real {
+            (*z - self.0).len() - 1.0
+        }
+    }
+
+    #[test]
+    fn round() {
+        let rounded = Round(Cube::new(&vec3!(1)), 0.5);
+        assert_eq!(rounded.distance_estimator(&vec3!(2, 0, 0)), 0.5);
+        assert_eq!(rounded.distance_estimator(&vec3!(2, 2, 1)), Real::sqrt(2.0) - 0.5);
+    }
+
+    #[test]
+    fn onion() {
+        let shell = Onion(Sphere::new(2.0), 0.25);
+        assert_eq!(shell.distance_estimator(&vec3!(0)), 1.75);
+        assert_eq!(shell.distance_estimator(&vec3!(0, 2, 0)), -0.25);
+        assert_eq!(shell.distance_estimator(&vec3!(0, 3, 0)), 0.75);
+    }
+
+    #[test]
+    fn elongate() {
+        // a sphere stretched along x is a capsule
+        let capsule = Elongate(Sphere::new(1.0), vec3!(2, 0, 0));
+        assert_eq!(capsule.distance_estimator(&vec3!(1.5, 3, 0)), 2.0);
+        assert_eq!(capsule.distance_estimator(&vec3!(5, 0, 0)), 2.0);
+        assert_eq!(capsule.distance_estimator(&vec3!(0)), -1.0);
+    }
+
+    #[test]
+    fn mirror() {
+        let mirrored = Mirror(Offset(vec3!(3, 0, 0)), [true, false, false]);
+        assert_eq!(mirrored.distance_estimator(&vec3!(-3, 0, 0)), -1.0);
+        assert_eq!(mirrored.distance_estimator(&vec3!(3, 0, 0)), -1.0);
+        // the other axes are left alone
+        assert_eq!(mirrored.distance_estimator(&vec3!(3, -2, 0)), 1.0);
+    }
+
+    #[test]
+    fn mirror_across_two_axes() {
+        // one sphere in each quadrant of the xz-plane
+        let mirrored = Mirror(Offset(vec3!(3, 0, 3)), [true, false, true]);
+        for p in [vec3!(3, 0, 3), vec3!(-3, 0, 3), vec3!(3, 0, -3), vec3!(-3, 0, -3)].iter() {
+            assert_eq!(mirrored.distance_estimator(p), -1.0);
+        }
+        assert_eq!(mirrored.distance_estimator(&vec3!(3, -3, 3)), 2.0);
+    }
+
+    #[test]
+    fn rotate() {
+        let cube = Cube::new(&vec3!(2, 1, 1));
+        let turned = Rotate(cube, Quat::from_axis_angle(&vec3!(0, 0, 1), crate::linalg::consts::FRAC_PI_2));
+        // the long side now points along y
+        assert!((turned.distance_estimator(&vec3!(0, 3, 0)) - 1.0).abs() < EPSILON);
+        assert!((turned.distance_estimator(&vec3!(3, 0, 0)) - 2.0).abs() < EPSILON);
+    }
+}
@@ -1,19 +1,33 @@
-use crate::linalg::Vec3;
+use crate::linalg::{Real, Scalar, Dual3, Packet, Vec3};
 use crate::objects::base::Object;
 
 pub struct Sphere {
-    r: f64
+    r: Real
 }
 
 impl Sphere {
-    pub fn new(r: f64) -> Self {
+    pub fn new(r: Real) -> Self {
         Self {r}
     }
 }
 
+impl Sphere {
+    fn distance<S: Scalar>(&self, z: &Vec3<S>) -> S {
+        z.len() - S::from_real(self.r)
+    }
+}
+
 impl Object for Sphere {
-    fn distance_estimator(&self, z: &Vec3) -> f64 {
-        z.len() - self.r
+    fn distance_estimator(&self, z: &Vec3) -> Real {
+        self.distance(z)
+    }
+
+    fn distance_dual(&self, z: &Vec3<Dual3>) -> Dual3 {
+        self.distance(z)
+    }
+
+    fn distance_packet(&self, z: &Vec3<Packet>) -> Packet {
+        self.distance(z)
     }
 }
 
@@ -25,13 +39,29 @@ pub struct Cube {
 
 impl Cube {
     pub fn new(b: &Vec3) -> Self {
-        Self {b: b.clone()}
+        Self {b: *b}
+    }
+}
+
+impl Cube {
+    pub(crate) fn distance<S: Scalar>(&self, z: &Vec3<S>) -> S {
+        let b = self.b.lift();
+        let zero = S::from_real(0.);
+        let d = z.abs() - b;
+        d.max(zero).len() + d.y.max(d.z).max(d.x).min(zero)
     }
 }
 
 impl Object for Cube {
-    fn distance_estimator(&self, z: &Vec3) -> f64 {
-        let d = z.abs() - self.b;
-        d.max(0.).len() + d.y.max(d.z).max(d.x).min(0.)
+    fn distance_estimator(&self, z: &Vec3) -> Real {
+        self.distance(z)
+    }
+
+    fn distance_dual(&self, z: &Vec3<Dual3>) -> Dual3 {
+        self.distance(z)
+    }
+
+    fn distance_packet(&self, z: &Vec3<Packet>) -> Packet {
+        self.distance(z)
     }
 }
\ No newline at end of file
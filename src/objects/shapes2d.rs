@@ -0,0 +1,364 @@
+use crate::linalg::consts::PI;
+use crate::linalg::{Real, Scalar, Dual3, Vec2, Vec3};
+use crate::objects::base::Object;
+
+// Signed distance to a shape in the plane. These don't go into a Scene by
+// themselves, but become objects through Extrude and Revolve
+pub trait Object2D {
+    fn distance_estimator(&self, p: &Vec2) -> Real;
+
+    // Like Object::distance_dual, with the same central differences fallback
+    fn distance_dual(&self, p: &Vec2<Dual3>) -> Dual3 {
+        let q = vec2!(p.x.value, p.y.value);
+        let h = Real::EPSILON.cbrt();
+        let diff = |e: Vec2| (self.distance_estimator(&(q + e)) - self.distance_estimator(&(q - e))) / (2.0 * h);
+        let gradient = p.x.derivative * diff(vec2!(h, 0)) + p.y.derivative * diff(vec2!(0, h));
+        Dual3::new(self.distance_estimator(&q), gradient)
+    }
+}
+
+fn rotate<S: Scalar>(p: &Vec2<S>, angle: S) -> Vec2<S> {
+    let (s, c) = (angle.sin(), angle.cos());
+    Vec2::new(c * p.x - s * p.y, s * p.x + c * p.y)
+}
+
+fn rem_euclid<S: Scalar>(x: S, m: S) -> S {
+    let r = x % m;
+    r.select_lt(S::from_real(0.0), r + m, r)
+}
+
+pub struct Circle {
+    r: Real
+}
+
+impl Circle {
+    pub fn new(r: Real) -> Self {
+        Self {r}
+    }
+
+    fn distance<S: Scalar>(&self, p: &Vec2<S>) -> S {
+        p.len() - S::from_real(self.r)
+    }
+}
+
+impl Object2D for Circle {
+    fn distance_estimator(&self, p: &Vec2) -> Real {
+        self.distance(p)
+    }
+
+    fn distance_dual(&self, p: &Vec2<Dual3>) -> Dual3 {
+        self.distance(p)
+    }
+}
+
+// b is the half size, like for Cube
+pub struct Rectangle {
+    b: Vec2
+}
+
+impl Rectangle {
+    pub fn new(b: &Vec2) -> Self {
+        Self {b: *b}
+    }
+
+    fn distance<S: Scalar>(&self, p: &Vec2<S>) -> S {
+        let zero = S::from_real(0.);
+        let d = p.abs() - self.b.lift();
+        d.max(zero).len() + d.x.max(d.y).min(zero)
+    }
+}
+
+impl Object2D for Rectangle {
+    fn distance_estimator(&self, p: &Vec2) -> Real {
+        self.distance(p)
+    }
+
+    fn distance_dual(&self, p: &Vec2<Dual3>) -> Dual3 {
+        self.distance(p)
+    }
+}
+
+pub struct RoundedRectangle {
+    inner: Rectangle,
+    r: Real
+}
+
+impl RoundedRectangle {
+    // The corners are rounded with radius r without growing the rectangle
+    pub fn new(b: &Vec2, r: Real) -> Self {
+        assert!(0.0 <= r && r <= b.x.min(b.y), "the corner radius must fit in the rectangle");
+        Self {inner: Rectangle::new(&(*b - r)), r}
+    }
+}
+
+impl Object2D for RoundedRectangle {
+    fn distance_estimator(&self, p: &Vec2) -> Real {
+        self.inner.distance_estimator(p) - self.r
+    }
+
+    fn distance_dual(&self, p: &Vec2<Dual3>) -> Dual3 {
+        self.inner.distance_dual(p) - Dual3::constant(self.r)
+    }
+}
+
+// n sides with the midpoint of each at distance r from the center,
+// the first one facing up
+pub struct RegularPolygon {
+    r: Real,
+    n: u32
+}
+
+impl RegularPolygon {
+    pub fn new(r: Real, n: u32) -> Self {
+        assert!(n >= 3, "a polygon needs at least 3 sides");
+        Self {r, n}
+    }
+
+    fn distance<S: Scalar>(&self, p: &Vec2<S>) -> S {
+        let half_angle = S::from_real(PI / self.n as Real);
+        let half_edge = S::from_real(self.r * (PI / self.n as Real).tan());
+        let r = S::from_real(self.r);
+        // rotate the sector p is in onto the one facing +x
+        let p = Vec2::new(p.y, -p.x);
+        let angle = p.y.atan2(p.x) + half_angle;
+        let p = rotate(&p, rem_euclid(angle, half_angle + half_angle) - angle);
+        let edge = Vec2::new(r, p.y.max(-half_edge).min(half_edge));
+        (p - edge).len() * (p.x - r).signum()
+    }
+}
+
+impl Object2D for RegularPolygon {
+    fn distance_estimator(&self, p: &Vec2) -> Real {
+        self.distance(p)
+    }
+
+    fn distance_dual(&self, p: &Vec2<Dual3>) -> Dual3 {
+        self.distance(p)
+    }
+}
+
+// A circular arc of radius r, opening symmetrically around the y axis by
+// aperture radians to either side, thickened by thickness
+pub struct Arc {
+    sin_cos: Vec2,
+    r: Real,
+    thickness: Real
+}
+
+impl Arc {
+    pub fn new(aperture: Real, r: Real, thickness: Real) -> Self {
+        let (s, c) = aperture.sin_cos();
+        Self {sin_cos: vec2!(s, c), r, thickness}
+    }
+
+    fn distance<S: Scalar>(&self, p: &Vec2<S>) -> S {
+        let p = Vec2::new(p.x.abs(), p.y);
+        let sc = self.sin_cos.lift::<S>();
+        let r = S::from_real(self.r);
+        // past the end of the arc, the endpoint is closest
+        let d = (sc.x * p.y).select_lt(sc.y * p.x, (p - sc * r).len(), (p.len() - r).abs());
+        d - S::from_real(self.thickness)
+    }
+}
+
+impl Object2D for Arc {
+    fn distance_estimator(&self, p: &Vec2) -> Real {
+        self.distance(p)
+    }
+
+    fn distance_dual(&self, p: &Vec2<Dual3>) -> Dual3 {
+        self.distance(p)
+    }
+}
+
+// A star with n points reaching out to r. sharpness goes from 2, giving
+// a regular polygon, up to n, giving the thinnest possible spikes
+pub struct Star {
+    r: Real,
+    n: u32,
+    sharpness: Real
+}
+
+impl Star {
+    pub fn new(r: Real, n: u32, sharpness: Real) -> Self {
+        assert!(n >= 3, "a star needs at least 3 points");
+        assert!((2.0..=n as Real).contains(&sharpness), "the sharpness of a star goes from 2 to its number of points");
+        Self {r, n, sharpness}
+    }
+
+    fn distance<S: Scalar>(&self, p: &Vec2<S>) -> S {
+        let an = PI / self.n as Real;
+        let en = PI / self.sharpness;
+        let acs = vec2!(an.cos(), an.sin()).lift::<S>();
+        let ecs = vec2!(en.cos(), en.sin()).lift::<S>();
+        let r = S::from_real(self.r);
+        // fold into half a spike
+        let bn = rem_euclid(p.x.atan2(p.y), S::from_real(2.0 * an)) - S::from_real(an);
+        let p = Vec2::new(bn.cos(), bn.sin().abs()) * p.len() - acs * r;
+        let p = p + ecs * (-p.dot(&ecs)).max(S::from_real(0.0)).min(r * acs.y / ecs.y);
+        p.len() * p.x.signum()
+    }
+}
+
+impl Object2D for Star {
+    fn distance_estimator(&self, p: &Vec2) -> Real {
+        self.distance(p)
+    }
+
+    fn distance_dual(&self, p: &Vec2<Dual3>) -> Dual3 {
+        self.distance(p)
+    }
+}
+
+// Sweeps the shape along z from -depth to depth
+pub struct Extrude<T: Object2D>(pub T, pub Real);
+
+impl<T: Object2D> Extrude<T> {
+    // d is the distance to the shape in the plane through z
+    fn extrude<S: Scalar>(&self, d: S, z: &Vec3<S>) -> S {
+        let zero = S::from_real(0.0);
+        let w = Vec2::new(d, z.z.abs() - S::from_real(self.1));
+        w.x.max(w.y).min(zero) + w.max(zero).len()
+    }
+}
+
+impl<T: Object2D> Object for Extrude<T> {
+    fn distance_estimator(&self, z: &Vec3) -> Real {
+        self.extrude(self.0.distance_estimator(&z.xy()), z)
+    }
+
+    fn distance_dual(&self, z: &Vec3<Dual3>) -> Dual3 {
+        self.extrude(self.0.distance_dual(&z.xy()), z)
+    }
+}
+
+// Spins the shape around the y axis, the x axis of the shape pointing
+// away from the axis after moving it out by offset
+pub struct Revolve<T: Object2D>(pub T, pub Real);
+
+impl<T: Object2D> Revolve<T> {
+    fn profile<S: Scalar>(&self, z: &Vec3<S>) -> Vec2<S> {
+        Vec2::new(z.xz().len() - S::from_real(self.1), z.y)
+    }
+}
+
+impl<T: Object2D> Object for Revolve<T> {
+    fn distance_estimator(&self, z: &Vec3) -> Real {
+        self.0.distance_estimator(&self.profile(z))
+    }
+
+    fn distance_dual(&self, z: &Vec3<Dual3>) -> Dual3 {
+        self.0.distance_dual(&self.profile(z))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::linalg::approx::EPSILON;
+    use crate::objects::base::tests::check_gradient;
+
+    fn close(a: Real, b: Real) -> bool {
+        (a - b).abs() < EPSILON
+    }
+
+    #[test]
+    fn rectangle() {
+        let rect = Rectangle::new(&vec2!(2, 1));
+        assert_eq!(rect.distance_estimator(&vec2!(0)), -1.0);
+        assert_eq!(rect.distance_estimator(&vec2!(5, 0)), 3.0);
+        assert_eq!(rect.distance_estimator(&vec2!(5, 5)), 5.0);
+    }
+
+    #[test]
+    fn rounded_rectangle_keeps_its_size() {
+        let rect = RoundedRectangle::new(&vec2!(2, 1), 0.5);
+        assert_eq!(rect.distance_estimator(&vec2!(3, 0)), 1.0);
+        assert!(rect.distance_estimator(&vec2!(2, 1)) > 0.0);
+    }
+
+    #[test]
+    #[should_panic(expected = "corner radius")]
+    fn rounded_rectangle_rejects_a_large_radius() {
+        RoundedRectangle::new(&vec2!(2, 1), 1.5);
+    }
+
+    #[test]
+    fn regular_polygon_apothem() {
+        let hexagon = RegularPolygon::new(1.0, 6);
+        for i in 0..6 {
+            // the middle of every edge
+            let angle = PI / 2.0 + i as Real * PI / 3.0;
+            let mid = vec2!(angle.cos(), angle.sin());
+            assert!(close(hexagon.distance_estimator(&mid), 0.0));
+            assert!(close(hexagon.distance_estimator(&(mid * 2.0)), 1.0));
+        }
+        assert!(close(hexagon.distance_estimator(&vec2!(0)), -1.0));
+    }
+
+    #[test]
+    fn arc() {
+        let arc = Arc::new(PI / 2.0, 2.0, 0.1);
+        // top of the arc, and the open bottom
+        assert!(close(arc.distance_estimator(&vec2!(0, 2)), -0.1));
+        assert!(close(arc.distance_estimator(&vec2!(0, -2)), Real::sqrt(2.0) * 2.0 - 0.1));
+    }
+
+    #[test]
+    fn star_tips() {
+        let star = Star::new(1.0, 5, 3.0);
+        for i in 0..5 {
+            let angle = PI / 2.0 - i as Real * 2.0 * PI / 5.0;
+            let tip = vec2!(angle.cos(), angle.sin());
+            assert!(close(star.distance_estimator(&tip), 0.0));
+        }
+        assert!(star.distance_estimator(&vec2!(0)) < 0.0);
+    }
+
+    #[test]
+    #[should_panic(expected = "at least 3 sides")]
+    fn polygon_needs_three_sides() {
+        RegularPolygon::new(1.0, 2);
+    }
+
+    #[test]
+    #[should_panic(expected = "at least 3 points")]
+    fn star_needs_three_points() {
+        Star::new(1.0, 2, 2.0);
+    }
+
+    #[test]
+    #[should_panic(expected = "sharpness")]
+    fn star_rejects_a_sharpness_past_its_points() {
+        Star::new(1.0, 5, 6.0);
+    }
+
+    #[test]
+    fn extrude() {
+        let slab = Extrude(Circle::new(1.0), 0.5);
+        assert_eq!(slab.distance_estimator(&vec3!(0, 0, 2)), 1.5);
+        assert_eq!(slab.distance_estimator(&vec3!(3, 0, 0)), 2.0);
+        assert_eq!(slab.distance_estimator(&vec3!(0)), -0.5);
+    }
+
+    #[test]
+    fn revolved_circle_is_a_torus() {
+        let torus = Revolve(Circle::new(0.5), 2.0);
+        assert_eq!(torus.distance_estimator(&vec3!(2, 0, 0)), -0.5);
+        assert_eq!(torus.distance_estimator(&vec3!(0, 0, -3)), 0.5);
+        assert_eq!(torus.distance_estimator(&vec3!(0, 0, 0)), 1.5);
+    }
+
+    #[test]
+    fn exact_gradients() {
+        let p = vec3!(0.9, 0.7, 0.4);
+        check_gradient(&Extrude(Circle::new(0.5), 0.2), &p);
+        check_gradient(&Extrude(RoundedRectangle::new(&vec2!(0.6, 0.4), 0.1), 1.0), &p);
+        check_gradient(&Extrude(RegularPolygon::new(1.0, 5), 1.0), &p);
+        check_gradient(&Extrude(Arc::new(1.0, 1.0, 0.1), 1.0), &p);
+        check_gradient(&Extrude(Arc::new(2.0, 1.0, 0.1), 1.0), &p);
+        check_gradient(&Extrude(Star::new(1.0, 5, 3.0), 1.0), &p);
+        check_gradient(&Revolve(Rectangle::new(&vec2!(0.2, 0.3)), 1.0), &p);
+        check_gradient(&Revolve(Star::new(0.5, 6, 2.5), 0.5), &vec3!(-0.3, 0.2, 0.9));
+    }
+}